@@ -1,11 +1,69 @@
 'use strict';
 
+const _ = require('lodash');
+const Promise = require('bluebird');
 const {MongoClient} = require('mongodb');
 
+const log = require('../log').child({
+    type: 'plebiscite'
+});
+
 const MONGO_URL = process.env.MONGO_URL;
 const MONGO_COLLECTION = process.env.MONGO_COLLECTION;
 
-const mdb_delayed = MongoClient.connect(MONGO_URL);
+const RETRY_ATTEMPTS = 3;
+const RETRY_BASE_DELAY = 100;
+
+// NotMaster, NotMasterNoSlaveOk, NotMasterOrSecondary
+const RETRYABLE_CODES = new Set([10107, 13435, 13436]);
+
+// compares substance names case-insensitively (i.e. `lsd` -> `LSD`)
+const SUBSTANCE_COLLATION = {locale: 'en', strength: 2};
+
+const mdb_delayed = MongoClient.connect(MONGO_URL, {
+    useUnifiedTopology: true,
+
+    poolSize: parseInt(process.env.MONGO_POOL_SIZE, 10),
+    minSize: parseInt(process.env.MONGO_MIN_POOL_SIZE, 10),
+
+    connectTimeoutMS: parseInt(process.env.MONGO_CONNECT_TIMEOUT_MS, 10),
+    serverSelectionTimeoutMS: parseInt(process.env.MONGO_SERVER_SELECTION_TIMEOUT_MS, 10)
+}).then(client =>
+    // the database is taken from the connection string
+    client.db()
+).catch(err => {
+    /*
+     * Plebiscite is optional: a Mongo outage must not take
+     * the substance data down with it, so the feature only
+     * reports itself as unavailable.
+     */
+    log.error(err, 'Could not connect to Mongo, Plebiscite is unavailable');
+
+    return null;
+});
+
+/*
+ * Substance filters are only served by an index whose collation
+ * matches theirs. Creating it requires write privileges, so it
+ * is left to operators, either by hand:
+ *
+ *   db.plebiscite.createIndex(
+ *       {'substanceInfo.substance': 1},
+ *       {collation: {locale: 'en', strength: 2}, background: true}
+ *   )
+ *
+ * or by opting in with `MONGO_CREATE_INDEXES` on startup.
+ */
+if (_.has(process.env, 'MONGO_CREATE_INDEXES')) {
+    mdb_delayed
+        .then(db => db && db.collection(MONGO_COLLECTION).createIndex(
+            {'substanceInfo.substance': 1},
+            {collation: SUBSTANCE_COLLATION, background: true}
+        ))
+        .catch(err => {
+            log.warn(err, 'Could not create the substance index');
+        });
+}
 
 class Plebiscite {
     constructor({db}) {
@@ -13,7 +71,45 @@ class Plebiscite {
     }
 
     * _getCollection() {
-        return (yield this._db).collection(MONGO_COLLECTION);
+        const db = yield this._db;
+
+        if (db === null) {
+            throw new Error('Plebiscite: the experience database is currently unavailable.');
+        }
+
+        return db.collection(MONGO_COLLECTION);
+    }
+
+    * isAvailable() {
+        return (yield this._db) !== null;
+    }
+
+    /*
+     * Timeouts are not retried: server selection has already
+     * waited for the configured timeout by the time one is
+     * raised, so retrying would only prolong an outage.
+     */
+    static _isRetryable(err) {
+        return err.name === 'MongoNetworkError'
+            || RETRYABLE_CODES.has(err.code);
+    }
+
+    * _withRetry(operation) {
+        for (let attempt = 1; ; ++attempt) {
+            try {
+                return yield operation();
+            } catch (err) {
+                if (attempt >= RETRY_ATTEMPTS || !Plebiscite._isRetryable(err)) {
+                    throw err;
+                }
+
+                const delay = RETRY_BASE_DELAY * Math.pow(2, attempt - 1);
+
+                log.warn(err, 'Mongo operation failed, retrying in %sms (attempt %s/%s)', delay, attempt, RETRY_ATTEMPTS);
+
+                yield Promise.delay(delay);
+            }
+        }
     }
 
     * find({substance, offset, limit}) {
@@ -21,15 +117,18 @@ class Plebiscite {
 
         const query = {};
 
-        if (!substance === true) {
-            query['substanceInfo.substance'] = substance;
+        if (substance) {
+            query['substanceInfo.substance'] = substance.trim();
         }
 
-        return yield collection.find(query)
-            .sort({'meta.published': -1})
-            .skip(offset)
-            .limit(limit)
-            .toArray();
+        return yield* this._withRetry(() =>
+            collection.find(query)
+                .collation(SUBSTANCE_COLLATION)
+                .sort({'meta.published': -1})
+                .skip(offset)
+                .limit(limit)
+                .toArray()
+        );
     }
 }
 
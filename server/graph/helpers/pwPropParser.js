@@ -2,9 +2,16 @@
 
 const _ = require('lodash');
 
+const baseLog = require('../../log');
+
 class PWPropParser {
-    constructor({smwDataArbitrator}) {
+    constructor({smwDataArbitrator, substanceBoxParser, log = baseLog}) {
         this._smwDataArbitrator = smwDataArbitrator;
+        this._substanceBoxParser = substanceBoxParser;
+
+        this._log = log.child({
+            type: 'PWPropParser'
+        });
 
         this._rgx = {
             /* durations */
@@ -38,8 +45,7 @@ class PWPropParser {
             ['addiction_potential', 'addictionPotential'],
             ['uncertaininteraction', 'uncertainInteractions'],
             ['unsafeinteraction', 'unsafeInteractions'],
-            ['dangerousinteraction', 'dangerousInteractions'],
-            ['effect', 'effects']
+            ['dangerousinteraction', 'dangerousInteractions']
         ]);
 
         this._mappedMetaProps = new Map([
@@ -58,8 +64,16 @@ class PWPropParser {
 
                 return [propTarget, mappedCrossTolerance];
             }],
-            ['featured', prop => ['featured', prop === 't']],
+            ['featured', prop => ['featured', prop === true || prop === 't']],
             ['toxicity', prop => (['toxicity', [].concat(prop)])],
+            [
+                'effect',
+                prop => ([
+                    'effects',
+                    // page values arrive as `Pupil_dilation#`
+                    [].concat(prop).map(prop => prop.replace(/#$/, '').replace(/_/g, ' ')),
+                ]),
+            ],
             [
                 'psychoactive_class',
                 prop => ([
@@ -147,122 +161,175 @@ class PWPropParser {
         return propValue;
     }
 
-    parse(propSet) {
-        const procPropMap = {};
-
-        propSet[1].map(([_propName, {prop}]) => {
-            const propName = _propName.toLowerCase();
+    // doses, durations and bioavailabilities are exposed as floats
+    _assertNumeric(propName, prop) {
+        if (!_.isNumber(prop)) {
+            throw new TypeError(`Property \`${propName}\` is not numeric: ${prop}`);
+        }
 
-            let rx;
+        return prop;
+    }
 
-            switch (true) {
-                /* durations */
-                case this._rgx.range_dur.test(propName):
-                    rx = this._rgx.range_dur.exec(propName);
+    _processProp(procPropMap, _propName, prop) {
+        const propName = _propName.toLowerCase();
 
-                    _.set(
-                        procPropMap,
-                        `roa.${rx[1]}.duration.${rx[3]}.${rx[2]}`,
-                        prop
-                    );
+        let rx;
+        let handled = true;
 
-                    break;
+        switch (true) {
+            /* durations */
+            case this._rgx.range_dur.test(propName):
+                rx = this._rgx.range_dur.exec(propName);
 
-                    /* doses */
+                _.set(
+                    procPropMap,
+                    `roa.${rx[1]}.duration.${rx[3]}.${rx[2]}`,
+                    this._assertNumeric(propName, prop)
+                );
 
-                case this._rgx.range_dose.test(propName):
-                    rx = this._rgx.range_dose.exec(propName);
+                break;
 
-                    _.set(
-                        procPropMap,
-                        `roa.${rx[1]}.dose.${rx[3]}.${rx[2]}`,
-                        prop
-                    );
+                /* doses */
 
-                    break;
+            case this._rgx.range_dose.test(propName):
+                rx = this._rgx.range_dose.exec(propName);
 
-                case this._rgx.def_dose.test(propName):
-                    rx = this._rgx.def_dose.exec(propName);
-
-                    _.set(
-                        procPropMap,
-                        `roa.${rx[1]}.dose.${rx[2]}`,
-                        prop
-                    );
+                _.set(
+                    procPropMap,
+                    `roa.${rx[1]}.dose.${rx[3]}.${rx[2]}`,
+                    this._assertNumeric(propName, prop)
+                );
 
-                    break;
+                break;
 
-                case this._rgx.def_bioavailability.test(propName):
-                    rx = this._rgx.def_bioavailability.exec(propName);
+            case this._rgx.def_dose.test(propName):
+                rx = this._rgx.def_dose.exec(propName);
 
-                    _.set(
-                        procPropMap,
-                        `roa.${rx[1]}.bioavailability.${rx[2]}`,
-                        prop
-                    );
+                _.set(
+                    procPropMap,
+                    `roa.${rx[1]}.dose.${rx[2]}`,
+                    this._assertNumeric(propName, prop)
+                );
 
-                    break;
+                break;
 
-                    /* units */
+            case this._rgx.def_bioavailability.test(propName):
+                rx = this._rgx.def_bioavailability.exec(propName);
 
-                case this._rgx.dose_units.test(propName):
-                    rx = this._rgx.dose_units.exec(propName);
+                _.set(
+                    procPropMap,
+                    `roa.${rx[1]}.bioavailability.${rx[2]}`,
+                    this._assertNumeric(propName, prop)
+                );
 
-                    _.set(
-                        procPropMap,
-                        `roa.${rx[1]}.dose.units`,
-                        prop
-                    );
+                break;
 
-                    break;
+                /* units */
 
-                case this._rgx.roa_time_units.test(propName):
-                    rx = this._rgx.roa_time_units.exec(propName);
+            case this._rgx.dose_units.test(propName):
+                rx = this._rgx.dose_units.exec(propName);
 
-                    _.set(
-                        procPropMap,
-                        `roa.${rx[1]}.duration.${rx[2]}.units`,
-                        prop
-                    );
+                _.set(
+                    procPropMap,
+                    `roa.${rx[1]}.dose.units`,
+                    prop
+                );
 
-                    break;
+                break;
 
-                    /* meta */
+            case this._rgx.roa_time_units.test(propName):
+                rx = this._rgx.roa_time_units.exec(propName);
 
-                case this._rgx.meta_tolerance_time.test(propName):
-                    rx = this._rgx.meta_tolerance_time.exec(propName);
+                _.set(
+                    procPropMap,
+                    `roa.${rx[1]}.duration.${rx[2]}.units`,
+                    prop
+                );
 
-                    _.set(
-                        procPropMap,
-                        `tolerance.${rx[1]}`,
-                        prop
-                    );
+                break;
 
-                    break;
-            }
+                /* meta */
 
-            if (this._flatMetaProps.has(propName)) {
-                const mappedPropName = this._flatMetaProps.get(propName);
+            case this._rgx.meta_tolerance_time.test(propName):
+                rx = this._rgx.meta_tolerance_time.exec(propName);
 
                 _.set(
                     procPropMap,
-                    mappedPropName,
-                    this._sanitizedIfNeeded(mappedPropName, prop)
+                    `tolerance.${rx[1]}`,
+                    prop
                 );
-            }
 
-            if (this._mappedMetaProps.has(propName)) {
-                rx = this._mappedMetaProps.get(propName)(prop);
+                break;
 
-                _.set(
-                    procPropMap,
-                    rx[0],
-                    this._sanitizedIfNeeded(rx[0], rx[1])
-                );
+            default:
+                handled = false;
+        }
+
+        if (this._flatMetaProps.has(propName)) {
+            handled = true;
+
+            const mappedPropName = this._flatMetaProps.get(propName);
+
+            _.set(
+                procPropMap,
+                mappedPropName,
+                this._sanitizedIfNeeded(mappedPropName, prop)
+            );
+        }
+
+        if (this._mappedMetaProps.has(propName)) {
+            handled = true;
+
+            rx = this._mappedMetaProps.get(propName)(prop);
+
+            _.set(
+                procPropMap,
+                rx[0],
+                this._sanitizedIfNeeded(rx[0], rx[1])
+            );
+        }
+
+        return handled;
+    }
+
+    parse(propSet) {
+        const procPropMap = {};
+
+        const droppedProps = [];
+        const unhandledProps = [];
+
+        propSet[1].forEach(([propName, {prop}]) => {
+            /*
+             * The wiki data is edited by hand and not always
+             * consistent, so a single malformed property must
+             * not take the remaining properties down with it.
+             */
+            try {
+                if (!this._processProp(procPropMap, propName, prop)) {
+                    unhandledProps.push(propName);
+                }
+            } catch (err) {
+                droppedProps.push(propName);
             }
         });
 
-        // new ROA interface
+        if (droppedProps.length) {
+            this._log.warn('Dropped malformed properties of `%s`: %s', propSet[0], droppedProps.join(', '));
+        }
+
+        // surfaces wiki properties that are not mapped yet
+        if (unhandledProps.length) {
+            this._log.debug('Unhandled properties of `%s`: %s', propSet[0], unhandledProps.join(', '));
+        }
+
+        /*
+         * new ROA interface
+         *
+         * `roas` is derived from every route found in the data,
+         * while `roa` only exposes the routes modelled by the
+         * `SubstanceRoaTypes` schema type. Both share the same
+         * route objects, so `roas` is the complete view.
+         */
         const rawROAMap = _.get(procPropMap, 'roa', {});
 
         const mappedROAs = _.chain(rawROAMap)
@@ -272,12 +339,25 @@ class PWPropParser {
 
         _.assign(procPropMap, { roas: mappedROAs });
 
+        // substances without any route data expose neither view
+        if (!mappedROAs.length) {
+            _.assign(procPropMap, { roa: null, roas: null });
+        }
+
         return procPropMap;
     }
 
     parseFromSMW(obj) {
         return this.parse(this._smwDataArbitrator.parse(obj));
     }
+
+    parseFromWikitext(subject, wikitext) {
+        return this.parse(this._substanceBoxParser.parse(subject, wikitext));
+    }
+
+    infoboxTransclusion(wikitext) {
+        return this._substanceBoxParser.transclusionTarget(wikitext);
+    }
 }
 
 module.exports = PWPropParser;
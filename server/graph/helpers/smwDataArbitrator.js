@@ -7,11 +7,38 @@ class SMWDataArbitrator {
         return prop.replace(/#1?0#/, '');
     }
 
+    parseNumber(item) {
+        // tolerate editor formatting such as `~5`, `1,000` or `100mg`
+        const match = String(item)
+            .replace(/(\d),(?=\d{3}(?!\d))/g, '$1')
+            .match(/-?\d*\.?\d+(?:e[+-]?\d+)?/i);
+
+        if (match === null) {
+            return null;
+        }
+
+        return parseFloat(match[0]);
+    }
+
+    /*
+        SMW data item types, see SMWDataItem::TYPE_*:
+
+        1: number, 2: blob (text), 4: boolean, 6: time,
+        8: container (record), 9: wikipage
+    */
+
     _processDataItem({type, item}) {
         switch (type) {
             case 1:
-                return parseFloat(item);
+                return this.parseNumber(item);
 
+            case 2:
+                return String(item);
+
+            case 4:
+                return item === 't' || item === true;
+
+            case 8:
             case 9:
                 return this._stripSMWProp(item);
 
@@ -30,6 +57,15 @@ class SMWDataArbitrator {
             case 2:
                 return 'string';
 
+            case 4:
+                return 'boolean';
+
+            case 6:
+                return 'time';
+
+            case 8:
+                return 'container';
+
             case 9:
                 return 'property';
 
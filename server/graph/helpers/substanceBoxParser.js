@@ -0,0 +1,123 @@
+'use strict';
+
+class SubstanceBoxParser {
+    constructor({smwDataArbitrator}) {
+        this._smwDataArbitrator = smwDataArbitrator;
+
+        this._rgx = {
+            box_start: /\{\{\s*SubstanceBox\s*\|/i,
+            box_transclusion: /\{\{\s*(SubstanceBox\/[^|{}]+?)\s*\}\}/i,
+            numeric: /^[~≈]?\s*-?\d[\d,.]*\s*[a-zµ%]*$/i
+        };
+    }
+
+    // articles may embed their box as `{{SubstanceBox/<page>}}`
+    transclusionTarget(wikitext) {
+        const match = wikitext ? this._rgx.box_transclusion.exec(wikitext) : null;
+
+        if (match === null) {
+            return null;
+        }
+
+        return `Template:${match[1]}`;
+    }
+
+    _extractTemplate(wikitext) {
+        const match = this._rgx.box_start.exec(wikitext);
+
+        if (match === null) {
+            return null;
+        }
+
+        let depth = 0;
+
+        for (let i = match.index; i < wikitext.length - 1; ++i) {
+            const token = wikitext.slice(i, i + 2);
+
+            if (token === '{{') {
+                ++depth;
+                ++i;
+            } else if (token === '}}') {
+                --depth;
+                ++i;
+
+                if (depth === 0) {
+                    // strip the enclosing {{ and }}
+                    return wikitext.slice(match.index + 2, i - 1);
+                }
+            }
+        }
+
+        return null;
+    }
+
+    _splitParams(template) {
+        const params = [];
+
+        let depth = 0;
+        let current = '';
+
+        for (let i = 0; i < template.length; ++i) {
+            const char = template[i];
+
+            if (char === '{' || char === '[') {
+                ++depth;
+            } else if (char === '}' || char === ']') {
+                --depth;
+            }
+
+            if (char === '|' && depth === 0) {
+                params.push(current);
+                current = '';
+
+                continue;
+            }
+
+            current += char;
+        }
+
+        params.push(current);
+
+        // the first segment is the template name
+        return params.slice(1);
+    }
+
+    _processValue(value) {
+        if (this._rgx.numeric.test(value)) {
+            return this._smwDataArbitrator.parseNumber(value);
+        }
+
+        return value;
+    }
+
+    parse(subject, wikitext) {
+        const properties = [];
+
+        const template = wikitext ? this._extractTemplate(wikitext) : null;
+
+        if (template === null) {
+            return [subject, properties];
+        }
+
+        this._splitParams(template).forEach(param => {
+            const delimiter = param.indexOf('=');
+
+            if (delimiter === -1) {
+                return;
+            }
+
+            const propName = param.slice(0, delimiter).trim().replace(/\s+/g, '_');
+            const propValue = param.slice(delimiter + 1).trim();
+
+            if (!propName || !propValue) {
+                return;
+            }
+
+            properties.push([propName, {prop: this._processValue(propValue)}]);
+        });
+
+        return [subject, properties];
+    }
+}
+
+module.exports = SubstanceBoxParser;
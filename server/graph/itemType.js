@@ -11,6 +11,9 @@ const {
 const schema = require('./schema/rootQuery');
 
 const features = require('../util/features');
+const constants = require('../util/constants');
+
+const pkg = require('../../package.json');
 
 const _GeneratorFunction = (function*() {}).constructor;
 const crMap = obj =>
@@ -39,19 +42,69 @@ const baseResolvers = {
 
             return yield* ctx.substances.getSubstanceEffects(args);
         },
+        * effects_by_substance_count(data, args, ctx) {
+            ctx.args = args;
+
+            return yield* ctx.substances.getSubstanceEffectCount(args);
+        },
+        experiences(data, {substances_by_effect, effects_by_substance, substance}) {
+            if ([substances_by_effect, effects_by_substance, substance].filter(a => a).length >= 2) {
+                throw new Error('Experiences: `substances_by_effect`, `effects_by_substance` and `substance` are mutually exclusive.');
+            }
+
+            // deprecated, never implemented
+            return null;
+        },
+        version() {
+            return {
+                version: pkg.version,
+                commit: process.env.GIT_SHA || null,
+                buildDate: process.env.BUILD_DATE || null
+            };
+        },
+        * substances_by_names(data, args, ctx) {
+            ctx.args = args;
+
+            return yield* ctx.substances.getSubstancesByNames(args);
+        },
+        * substances_by_class(data, args, ctx) {
+            ctx.args = args;
+
+            return yield* ctx.substances.getClassSubstances(args);
+        },
         * substances_by_effect(data, args, ctx) {
             ctx.args = args;
 
             return yield* ctx.substances.getEffectSubstances(args);
+        },
+        * substances_by_bioavailability(data, args, ctx) {
+            ctx.args = args;
+
+            return yield* ctx.substances.getBioavailabilitySubstances(args);
+        },
+        * substance_suggestions(data, args, ctx) {
+            ctx.args = args;
+
+            return yield* ctx.substances.getSubstanceSuggestions(args);
+        },
+        * related_substances(data, args, ctx) {
+            ctx.args = args;
+
+            return yield* ctx.substances.getRelatedSubstances(args);
         }
     },
     Substance: {
         * effects(data, args, ctx) {
+            const effects = _.get(data, 'effects', null);
+
+            // the semantic data of the substance already lists its effects
+            if (effects !== null) {
+                return ctx.substances.mapPageNames([].concat(effects));
+            }
+
             const substance = _.get(data, 'name');
 
-            return yield* ctx.substances.getSubstanceEffects(
-                _.assign({}, {substance}, ctx.args)
-            );
+            return yield* ctx.substances.getSubstanceEffects({substance});
         },
 
         * uncertainInteractions(data, __, ctx) {
@@ -132,6 +185,20 @@ const baseResolvers = {
             ));
         },
 
+        interactionsCount(data) {
+            // counted from the raw names, without resolving the substances
+            const [uncertain, unsafe, dangerous] = [
+                'uncertainInteractions',
+                'unsafeInteractions',
+                'dangerousInteractions'
+            ].map(prop => [].concat(_.get(data, prop) || []).length);
+
+            return {
+                uncertain, unsafe, dangerous,
+                total: uncertain + unsafe + dangerous
+            };
+        },
+
         * summary(data, args, ctx) {
             const substance = _.get(data, 'name');
 
@@ -140,6 +207,12 @@ const baseResolvers = {
             );
         },
 
+        * lastModified(data, args, ctx) {
+            const substance = _.get(data, 'name');
+
+            return yield* ctx.substances.getSubstanceLastModified({substance});
+        },
+
         * images(data, args, ctx) {
             const substance = _.get(data, 'name');
 
@@ -179,8 +252,29 @@ class DeprecatedDirective extends SchemaDirectiveVisitor {
     }
 }
 
+const maxLimit = constants.get('maxLimit');
+const maxOffset = constants.get('maxOffset');
+
+const clampPagination = resolver =>
+    function (data, args, ...rest) {
+        const clampedArgs = _.assign({}, args);
+
+        if (_.isNumber(args.limit)) {
+            // a limit of 0 would lift the limit altogether upstream
+            clampedArgs.limit = _.clamp(args.limit, 1, maxLimit);
+        }
+
+        if (_.isNumber(args.offset)) {
+            clampedArgs.offset = _.clamp(args.offset, 0, maxOffset);
+        }
+
+        return resolver.call(this, data, clampedArgs, ...rest);
+    };
+
 const resolvers = crMap(baseResolvers);
 
+resolvers.Query = _.mapValues(resolvers.Query, clampPagination);
+
 class PwEdge {
     get schema() {
         return makeExecutableSchema({
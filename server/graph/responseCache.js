@@ -0,0 +1,104 @@
+'use strict';
+
+const crypto = require('crypto');
+
+const _ = require('lodash');
+const {print} = require('graphql');
+
+const pkg = require('../../package.json');
+
+// responses of a previous deployment must never be served
+const BUILD_ID = `${pkg.version}:${process.env.GIT_SHA || ''}`;
+
+/*
+    Apollo plugin caching the responses of read queries for a
+    short time, keyed by a hash of the normalized query text,
+    its variables and operation name. Popular queries (i.e. the
+    default playground query) are answered without resolving
+    the substances again.
+*/
+
+class ResponseCache {
+    constructor({ttl, maxEntries}) {
+        this._ttl = ttl;
+        this._maxEntries = maxEntries;
+
+        this._entries = new Map();
+    }
+
+    static key({document, request}) {
+        return crypto.createHash('sha256')
+            .update(JSON.stringify([
+                BUILD_ID,
+                print(document),
+                request.variables || {},
+                request.operationName || null
+            ]))
+            .digest('hex');
+    }
+
+    get(key) {
+        const entry = this._entries.get(key);
+
+        if (!entry) {
+            return null;
+        }
+
+        if ((Date.now() - entry.ts) > this._ttl) {
+            this._entries.delete(key);
+
+            return null;
+        }
+
+        return entry.data;
+    }
+
+    set(key, data) {
+        // maps iterate in insertion order, so the first key is the oldest
+        if (this._entries.size >= this._maxEntries) {
+            this._entries.delete(this._entries.keys().next().value);
+        }
+
+        this._entries.set(key, {ts: Date.now(), data});
+    }
+
+    plugin() {
+        const cache = this;
+
+        return {
+            requestDidStart() {
+                let missedKey = null;
+
+                return {
+                    responseForOperation(requestContext) {
+                        // only idempotent reads are cached
+                        if (_.get(requestContext, 'operation.operation') !== 'query') {
+                            return null;
+                        }
+
+                        const key = ResponseCache.key(requestContext);
+                        const data = cache.get(key);
+
+                        if (data === null) {
+                            missedKey = key;
+
+                            return null;
+                        }
+
+                        return {data};
+                    },
+
+                    willSendResponse({response}) {
+                        if (missedKey === null || !_.isEmpty(response.errors)) {
+                            return;
+                        }
+
+                        cache.set(missedKey, response.data);
+                    }
+                };
+            }
+        };
+    }
+}
+
+module.exports = ResponseCache;
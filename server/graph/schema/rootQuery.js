@@ -121,19 +121,38 @@ type SubstanceRoaTypes {
     intramuscular: SubstanceRoa
     intravenous: SubstanceRoa
     smoked: SubstanceRoa
+    vaporized: SubstanceRoa
+    inhaled: SubstanceRoa
+}
+
+type SubstanceImageThumb {
+    width: Int
+    url: String
 }
 
 type SubstanceImage {
     thumb: String
+    thumbs: [SubstanceImageThumb]
     image: String
 }
 
+type SubstanceInteractionsCount {
+    uncertain: Int
+    unsafe: Int
+    dangerous: Int
+
+    total: Int
+}
+
 type Substance {
     name: String
     url: String
 
     featured: Boolean
 
+    # ISO 8601 timestamp of the last revision of the article
+    lastModified: String
+
 	effects: [Effect]
 	experiences: [Experience]
 
@@ -154,6 +173,8 @@ type Substance {
     uncertainInteractions: [Substance]
     unsafeInteractions: [Substance]
     dangerousInteractions: [Substance]
+
+    interactionsCount: SubstanceInteractionsCount
 }
 
 type Effect {
@@ -169,6 +190,12 @@ type Experience {
 	effects: [Experience]
 }
 
+type BuildInfo {
+    version: String
+    commit: String
+    buildDate: String
+}
+
 ${plebisciteSchema}
 
 type Query {
@@ -200,6 +227,19 @@ type Query {
         offset: Int=0
     ): [Substance] @deprecated(reason: "This node will be removed soon. In order to fetch effect related information, use the specific nodes \`substances_by_effect\` or \`effects_by_substance\` instead.")
 
+    substances_by_names(
+        # Names of the substances you are looking for
+        names: [String!]!
+    ): [Substance]
+
+    substances_by_class(
+        # Name of the chemical or psychoactive class (i.e. lysergamide)
+        name: String!
+
+        limit: Int=50
+        offset: Int=0
+    ): [Substance]
+
     substances_by_effect(
         # Name of the effect you are looking for
         effect: [String]
@@ -208,6 +248,28 @@ type Query {
         offset: Int=0
     ): [Substance]
 
+    substances_by_bioavailability(
+        # Route of administration the bioavailability data is required for (i.e. oral)
+        roa: String!
+
+        limit: Int=10
+        offset: Int=0
+    ): [Substance]
+
+    substance_suggestions(
+        # Possibly misspelled name of the substance you are looking for
+        query: String!
+
+        limit: Int=5
+    ): [Substance]
+
+    related_substances(
+        # Name of the substance you want substances with a similar effect profile of
+        substance: String!
+
+        limit: Int=10
+    ): [Substance]
+
     effects_by_substance(
         # Name of the substance you want the effects of
     	substance: String
@@ -216,11 +278,20 @@ type Query {
         offset: Int=0
     ): [Effect]
 
+    # Total number of effects of a substance, for paginating \`effects_by_substance\`
+    effects_by_substance_count(
+        # Name of the substance you want the effect count of
+        substance: String
+    ): Int
+
     experiences(
     	substances_by_effect: String,
     	effects_by_substance: String,
     	substance: String
-    ): [Experience]
+    ): [Experience] @deprecated(reason: "This node was never implemented and always resolves to null. It will be removed soon.")
+
+    # Version and build information of this API deployment
+    version: BuildInfo
 
     ${plebisciteRootQuery}
 }
@@ -17,6 +17,41 @@ const qsDefaults = {
     format: 'json'
 };
 
+// thirty minutes
+const DEFAULT_CACHE_LIFETIME = 30 * 60 * 1000;
+
+// upper bound of requests waiting on the same upstream fetch, 0 is unbounded
+const MAX_COALESCED_WAITERS = parseInt(process.env.MAX_COALESCED_WAITERS, 10) || 0;
+
+/*
+    Cache lifetimes per request category, in minutes.
+
+    The category of a request is its action, suffixed
+    by the requested prop where applicable (i.e. `ask`
+    or `parse:images`). Every lifetime can be overridden
+    through the environment, i.e. `CACHE_TTL_PARSE_IMAGES`.
+*/
+const CACHE_CATEGORIES = [
+    'ask',
+    'browsebysubject',
+    'parse:images',
+    'parse:text',
+    'parse:wikitext',
+    'query:revisions'
+];
+
+const cacheLifetimes = new Map(
+    CACHE_CATEGORIES.map(category => {
+        const flag = `CACHE_TTL_${category.replace(':', '_').toUpperCase()}`;
+        const minutes = parseInt(process.env[flag], 10);
+
+        return [
+            category,
+            Number.isNaN(minutes) ? DEFAULT_CACHE_LIFETIME : minutes * 60 * 1000
+        ];
+    })
+);
+
 /*
     Caching algorithm:
 
@@ -44,11 +79,30 @@ class BifrostCache {
             type: 'bifrostCache'
         });
 
-        // thirty minutes
-        this._CACHE_LIFETIME = 30 * 60 * 1000;
-
         this._backend = new Map();
         this._processMap = new Map();
+        this._inflightMap = new Map();
+
+        this._events = {
+            hit: 0,
+            stale: 0,
+            miss: 0,
+            coalesced: 0,
+            rejected: 0
+        };
+    }
+
+    recordEvent(event) {
+        this._events[event]++;
+    }
+
+    stats() {
+        return _.assign({
+            size: this._backend.size,
+            refreshing: this._processMap.size,
+            inflight: this._inflightMap.size,
+            waiters: _.sumBy([...this._inflightMap.values()], 'waiters')
+        }, this._events);
     }
 
     /* this._log.trace('Key invalidated, removing: `%s` (ttl: %s)', key, Date.now() - item.ts); */
@@ -60,11 +114,11 @@ class BifrostCache {
             return null;
         }
 
-        const {ts, val} = cachedItem;
+        const {ts, lifetime, val} = cachedItem;
 
         let requireRefresh = false;
 
-        if ((Date.now() - ts) > this._CACHE_LIFETIME) {
+        if ((Date.now() - ts) > lifetime) {
             requireRefresh = true;
         }
 
@@ -83,11 +137,23 @@ class BifrostCache {
         return this._processMap.set(key, true);
     }
 
-    add(key, val) {
+    getInflight(key) {
+        return this._inflightMap.get(key) || null;
+    }
+
+    markInflight(key, promise) {
+        return this._inflightMap.set(key, {promise, waiters: 0});
+    }
+
+    clearInflight(key) {
+        return this._inflightMap.delete(key);
+    }
+
+    add(key, val, lifetime = DEFAULT_CACHE_LIFETIME) {
         this._log.trace('Adding key: `%s\'', key);
 
         return this._backend.set(key, {
-            ts: Date.now(), val
+            ts: Date.now(), lifetime, val
         });
     }
 }
@@ -96,6 +162,59 @@ const sharedBifrostCache = new BifrostCache({
     log: baseLog
 });
 
+/*
+    Token bucket bounding the total request rate towards
+    the wiki, shared by foreground fetches and background
+    refreshes alike. Disabled unless `UPSTREAM_RPS` is set.
+*/
+
+class UpstreamRateLimiter {
+    constructor({rps, burst}) {
+        this._rps = rps;
+        this._capacity = burst;
+
+        this._tokens = burst;
+        this._lastRefill = Date.now();
+    }
+
+    _refill() {
+        const now = Date.now();
+
+        this._tokens = Math.min(
+            this._capacity,
+            this._tokens + ((now - this._lastRefill) / 1000) * this._rps
+        );
+
+        this._lastRefill = now;
+    }
+
+    * acquire() {
+        if (!this._rps) {
+            return;
+        }
+
+        this._refill();
+
+        /*
+         * Reserve the token right away, even if it is
+         * yet to be refilled, so that concurrent callers
+         * queue up behind each other in order.
+         */
+        this._tokens -= 1;
+
+        if (this._tokens < 0) {
+            yield Promise.delay((-this._tokens / this._rps) * 1000);
+        }
+    }
+}
+
+const upstreamRps = parseFloat(process.env.UPSTREAM_RPS) || 0;
+
+const sharedRateLimiter = new UpstreamRateLimiter({
+    rps: upstreamRps,
+    burst: parseInt(process.env.UPSTREAM_BURST, 10) || Math.max(1, Math.ceil(upstreamRps))
+});
+
 class PwConnector {
     constructor({log}) {
         this._log = log.child({
@@ -103,10 +222,15 @@ class PwConnector {
         });
 
         this._cache = sharedBifrostCache;
+        this._rateLimiter = sharedRateLimiter;
+
+        this._maxCoalescedWaiters = MAX_COALESCED_WAITERS;
     }
 
-    _fetchUrl(url) {
-        return request({
+    * _fetchUrl(url) {
+        yield* this._rateLimiter.acquire();
+
+        return yield request({
             uri: url,
             json: true,
             gzip: true,
@@ -116,12 +240,24 @@ class PwConnector {
         });
     }
 
+    static _cacheLifetime(url) {
+        const {action, prop} = querystring.decode(url.split('?')[1]);
+
+        const category = prop ? `${action}:${prop}` : action;
+
+        if (!cacheLifetimes.has(category)) {
+            return DEFAULT_CACHE_LIFETIME;
+        }
+
+        return cacheLifetimes.get(category);
+    }
+
     * _fetchRefreshedCacheItem(url) {
         this._log.trace('Fetching item: `%s`', url);
 
-        const response = yield this._fetchUrl(url);
+        const response = yield* this._fetchUrl(url);
 
-        this._cache.add(url, response.body);
+        this._cache.add(url, response.body, PwConnector._cacheLifetime(url));
 
         return response.body;
     }
@@ -129,14 +265,14 @@ class PwConnector {
     * _getCacheIfNeeded(url) {
         const cacheState = this._cache.get(url);
 
-        /* todo: handle state when key doesnt exist and fetch is in progress */
-
         if (cacheState === null) {
-            return yield* this._fetchRefreshedCacheItem(url);
+            return yield* this._fetchCoalesced(url);
         }
 
         const {val, requireRefresh} = cacheState;
 
+        this._cache.recordEvent(requireRefresh ? 'stale' : 'hit');
+
         if (requireRefresh && !this._cache.isBeingRefreshed(url)) {
             this._unwindMarkAndRefreshItem(url);
 
@@ -146,6 +282,47 @@ class PwConnector {
         return val;
     }
 
+    * _fetchCoalesced(url) {
+        const inflight = this._cache.getInflight(url);
+
+        /*
+         * Concurrent misses of the same item wait for the
+         * fetch already in progress instead of issuing their
+         * own; past the configured number of waiters, further
+         * requests are rejected rather than piling up behind
+         * a slow upstream.
+         */
+        if (inflight !== null) {
+            if (this._maxCoalescedWaiters && inflight.waiters >= this._maxCoalescedWaiters) {
+                this._cache.recordEvent('rejected');
+
+                throw new Error('Too many pending requests for this item, please retry later.');
+            }
+
+            this._cache.recordEvent('coalesced');
+
+            inflight.waiters++;
+
+            try {
+                return yield inflight.promise;
+            } finally {
+                inflight.waiters--;
+            }
+        }
+
+        this._cache.recordEvent('miss');
+
+        const promise = Promise.coroutine(this._fetchRefreshedCacheItem).call(this, url);
+
+        this._cache.markInflight(url, promise);
+
+        try {
+            return yield promise;
+        } finally {
+            this._cache.clearInflight(url);
+        }
+    }
+
     * get(args) {
         const params = querystring.encode(_.defaults(args, qsDefaults));
 
@@ -179,11 +356,24 @@ class PwConnector {
 PwConnector.prototype._markAndRefreshItemAsync = Promise.coroutine(function* (url) {
     this._log.trace('[markAndRefreshAsync] Fetching item: `%s`', url);
 
-    yield* this._fetchRefreshedCacheItem(url);
+    /*
+     * The refresh flag has to be cleared even if the
+     * fetch fails, otherwise the item is never refreshed
+     * again and keeps serving its expired value forever.
+     */
+    try {
+        yield* this._fetchRefreshedCacheItem(url);
+    } catch (err) {
+        this._log.warn(err, '[markAndRefreshAsync] Failed to refresh item: `%s`', url);
+    } finally {
+        this._log.trace('[markAndRefreshAsync] Marking item as not being refreshed: `%s`', url);
+
+        this._cache.markBeingRefreshed(url, false);
+    }
+});
 
-    this._log.trace('[markAndRefreshAsync] Marking item as not being refreshed: `%s`', url);
+PwConnector.cacheStats = () => sharedBifrostCache.stats();
 
-    this._cache.markBeingRefreshed(url, false);
-});
+PwConnector.UpstreamRateLimiter = UpstreamRateLimiter;
 
 module.exports = PwConnector;
@@ -6,6 +6,7 @@ const Promise = require('bluebird');
 const _ = require('lodash');
 
 const constants = require('../../util/constants');
+const levenshtein = require('../../util/levenshtein');
 
 /*
     ABSTRACT GENERATION
@@ -61,7 +62,23 @@ class AbstractGenerator {
 */
 
 const cdnURL = constants.get('cdn');
+
 const thumbSize = constants.get('thumbSize');
+const thumbSizes = constants.get('thumbSizes');
+
+const thumbTemplate = constants.get('thumbTemplate');
+const imageTemplate = constants.get('imageTemplate');
+
+const renderTemplate = (template, values) =>
+    template.replace(/\{(\w+)\}/g, (match, key) =>
+        _.has(values, key) ? values[key] : match
+    );
+
+// MediaWiki file names are never empty and never contain path separators
+const isValidFileName = fileName =>
+    _.isString(fileName)
+    && fileName.trim().length > 0
+    && !/[/\\]/.test(fileName);
 
 const buildImage = fileName => {
     const fileNameHash = crypto.createHash('md5')
@@ -69,15 +86,35 @@ const buildImage = fileName => {
         .digest()
         .toString('hex');
 
-    const imageThumbnail = `${cdnURL}w/thumb.php?f=${fileName}&width=${thumbSize}`;
-    const imageURL = `${cdnURL}w/images/${fileNameHash[0]}/${fileNameHash.slice(0,2)}/${fileName}`;
+    // the hash is derived from the raw name, only the URL is encoded
+    const values = {
+        cdn: cdnURL,
+        filename: encodeURIComponent(fileName),
+        hash1: fileNameHash[0],
+        hash2: fileNameHash.slice(0,2)
+    };
+
+    const buildThumbnail = width =>
+        renderTemplate(thumbTemplate, _.assign({width}, values));
 
     return {
-        thumb: imageThumbnail,
-        image: imageURL
+        thumb: buildThumbnail(thumbSize),
+        thumbs: thumbSizes.map(width => ({
+            width,
+            url: buildThumbnail(width)
+        })),
+        image: renderTemplate(imageTemplate, values)
     };
 };
 
+// upper bound of substances considered when ranking across the whole index
+const SUBSTANCE_INDEX_LIMIT = 5000;
+
+// upper bound of concurrent semantic data requests per query
+const upstreamConcurrency = constants.get('upstreamConcurrency');
+
+const maxLimit = constants.get('maxLimit');
+
 class Substances {
     constructor({connector, pwPropParser, log}) {
         this._connector = connector;
@@ -102,6 +139,22 @@ class Substances {
         });
     }
 
+    mapPageNames(names) {
+        return names.map(name => ({
+            name,
+            url: `${cdnURL}wiki/${encodeURI(name.replace(/ /g, '_'))}`
+        }));
+    }
+
+    static _isMissingPage(res) {
+        return _.get(res, 'error.code') === 'missingtitle';
+    }
+
+    // subqueries keep the disjunction from binding to the conditions that follow
+    static _renderDisjunction(conditions) {
+        return `<q>${conditions.join(' OR ')}</q>`;
+    }
+
     static _renderPagination({limit, offset}) {
         return `${limit ? `|limit=${limit}` : ''}${offset ? `|offset=${offset}` : ''}`;
     }
@@ -114,15 +167,88 @@ class Substances {
             subject: substance
         });
 
-        return this._pwPropParser.parseFromSMW(res);
+        let semanticData;
+
+        try {
+            semanticData = this._pwPropParser.parseFromSMW(res);
+        } catch (err) {
+            this._log.warn(err, '[getSemanticSubstanceProps] Could not parse semantic data of `%s`', substance);
+
+            semanticData = {};
+        }
+
+        if (!_.isEmpty(semanticData.roas)) {
+            return semanticData;
+        }
+
+        /*
+         * Some articles carry their dosage information in the
+         * infobox without exposing it as semantic properties,
+         * so fall back to scraping the SubstanceBox template.
+         */
+        let infoboxData;
+
+        try {
+            infoboxData = yield* this.getSubstanceInfobox(substance);
+        } catch (err) {
+            this._log.warn(err, '[getSemanticSubstanceProps] Could not fetch infobox of `%s`', substance);
+
+            return semanticData;
+        }
+
+        // values are taken whole from either source, never mixed
+        return _.assign({}, infoboxData, _.omitBy(semanticData, _.isNull));
+    }
+
+    * _getWikitext(page) {
+        const wikitextPayload = yield* this._connector.get({
+            action: 'parse',
+            page,
+            prop: 'wikitext'
+        });
+
+        return _.get(wikitextPayload, 'parse.wikitext.*', null);
+    }
+
+    * getSubstanceInfobox(substance) {
+        this._log.trace('[getSubstanceInfobox] substance: %s', substance);
+
+        let wikitext = yield* this._getWikitext(substance);
+
+        const transclusion = this._pwPropParser.infoboxTransclusion(wikitext);
+
+        // the box lives on its own template page
+        if (transclusion !== null) {
+            wikitext = yield* this._getWikitext(transclusion);
+        }
+
+        try {
+            return this._pwPropParser.parseFromWikitext(substance, wikitext);
+        } catch (err) {
+            this._log.warn(err, '[getSubstanceInfobox] Could not parse infobox of `%s`', substance);
+
+            return {};
+        }
     }
 
     * getSubstances({chemicalClass, psychoactiveClass, effect, query, limit, offset}) {
-        if ([effect, query, chemicalClass, psychoactiveClass].filter(a => a).length >= 2) {
-            throw new Error('Substances: `chemicalClass`, `psychoactiveClass`, `effect` and `query` are mutually exclusive.');
+        // `effect` is accepted both as a single name and as a list of names
+        const effects = [].concat(effect || []);
+
+        const filterCount = [effects.length, chemicalClass, psychoactiveClass].filter(a => a).length;
+
+        if (query && filterCount) {
+            throw new Error('Substances: `query` is mutually exclusive with `chemicalClass`, `psychoactiveClass` and `effect`.');
         }
 
-        this._log.trace('[getSubstances] effect: %s query: %s chemicalClass: %s psychoactiveClass: %s', effect, query, psychoactiveClass);
+        this._log.trace('[getSubstances] effect: %s query: %s chemicalClass: %s psychoactiveClass: %s', effects, query, chemicalClass, psychoactiveClass);
+
+        /* intersect the filters in a single query */
+        if (filterCount >= 2) {
+            return yield* this.getFilteredSubstances({
+                effect: effects, chemicalClass, psychoactiveClass, limit, offset
+            });
+        }
 
         /* delegate to chemicalClass search */
         if (chemicalClass) {
@@ -139,9 +265,9 @@ class Substances {
         }
 
         /* Delegate the search to a specific substance query */
-        if (effect) {
+        if (effects.length) {
             return yield* this.getEffectSubstances({
-                effect, limit, offset
+                effect: effects, limit, offset
             });
         }
 
@@ -153,31 +279,129 @@ class Substances {
 
         const results = _.get(res, 'query.results', {});
 
+        return yield* this._withSemanticData(this._mapTextUrl(results));
+    }
+
+    * getSubstancesByNames({names}) {
+        if (names.length > maxLimit) {
+            throw new Error(`Substances: \`names\` accepts at most ${maxLimit} names.`);
+        }
+
+        this._log.trace('[getSubstancesByNames] names: %s', names);
+
         const self = this;
 
-        return yield Promise.all(
-            this._mapTextUrl(results).map(item =>
+        return yield Promise.map(
+            names,
+            Promise.coroutine(function* (name) {
+                const trimmedName = name.trim();
+
+                // a blank query would list every substance instead
+                if (!trimmedName) {
+                    return null;
+                }
+
+                const results = yield* self.getSubstances({
+                    query: trimmedName,
+                    limit: 1,
+                    offset: 0
+                });
+
+                // preserve the input order, yielding null for misses
+                return _.size(results) === 1 ? results[0] : null;
+            }),
+            {concurrency: upstreamConcurrency}
+        );
+    }
+
+    * _withSemanticData(items) {
+        const self = this;
+
+        return yield Promise.map(
+            items,
+            item =>
                 Promise.coroutine(function* (_item) {
                     const semanticData = yield* self.getSemanticSubstanceProps(_item.name);
 
                     process.env.DUMP_SEMANTICS && this._log.trace('Processed semantic data', semanticData);
 
                     return _.merge(item, semanticData);
-                }).call(this, item)
-            )
+                }).call(this, item),
+            {concurrency: upstreamConcurrency}
         );
     }
 
-    * getSubstanceEffects({substance, limit, offset}) {
-        this._log.trace('[getSubstanceEffects] substance: %s', substance);
+    static _bioavailabilityMidpoint(item) {
+        const bounds = [
+            _.head(_.get(item, 'printouts.min')),
+            _.head(_.get(item, 'printouts.max'))
+        ].filter(_.isNumber);
+
+        if (!bounds.length) {
+            return null;
+        }
+
+        return _.mean(bounds);
+    }
+
+    * getBioavailabilitySubstances({roa, limit, offset}) {
+        this._log.trace('[getBioavailabilitySubstances] roa: %s', roa);
+
+        const roaProp = _.upperFirst(roa.toLowerCase());
 
+        /*
+         * SMW cannot order by a derived value, so the bounds of
+         * every substance with bioavailability data for this route
+         * are fetched as printouts and sorted by the midpoint of
+         * their range. Only the requested page is then resolved.
+         */
         const res = yield* this._connector.get({
-            query: `[[:${substance}]]|?Effect${Substances._renderPagination({limit, offset})}`
+            query: `${Substances._renderDisjunction([
+                `[[${roaProp}_min_bioavailability::+]]`,
+                `[[${roaProp}_max_bioavailability::+]]`
+            ])}|[[Category:Psychoactive substance]]|?${roaProp}_min_bioavailability=min|?${roaProp}_max_bioavailability=max${Substances._renderPagination({limit: SUBSTANCE_INDEX_LIMIT})}`
         });
 
-        const results = _.get(res, `query.results.${substance}.printouts.Effect`, {});
+        const results = _.get(res, 'query.results', {});
 
-        return this._mapTextUrl(results);
+        const page = _.chain(results)
+            .map(item => [item, Substances._bioavailabilityMidpoint(item)])
+            .filter(([, midpoint]) => midpoint !== null)
+            .sortBy(([, midpoint]) => midpoint)
+            .map(([item]) => item)
+            .slice(offset, offset + limit)
+            .value();
+
+        return yield* this._withSemanticData(this._mapTextUrl(page));
+    }
+
+    * _getSubstanceEffectList(substance) {
+        const res = yield* this._connector.get({
+            query: `[[:${substance}]]|?Effect`
+        });
+
+        return _.get(res, ['query', 'results', substance, 'printouts', 'Effect'], []);
+    }
+
+    * getSubstanceEffects({substance, limit, offset = 0}) {
+        this._log.trace('[getSubstanceEffects] substance: %s', substance);
+
+        /*
+         * The effects are printouts of a single result, so the
+         * ask pagination does not apply to them and they have
+         * to be paginated here.
+         */
+        const results = yield* this._getSubstanceEffectList(substance);
+
+        return this._mapTextUrl(
+            _.slice(results, offset, limit ? offset + limit : undefined)
+        );
+    }
+
+    * getSubstanceEffectCount({substance}) {
+        this._log.trace('[getSubstanceEffectCount] substance: %s', substance);
+
+        return _.size(yield* this._getSubstanceEffectList(substance));
     }
 
     * getSubstanceAbstract({substance}) {
@@ -190,6 +414,13 @@ class Substances {
             section: 0
         });
 
+        // missing articles resolve to null rather than an empty summary
+        if (Substances._isMissingPage(abstractPayload)) {
+            this._log.trace('[getSubstanceAbstract] missing article: %s', substance);
+
+            return null;
+        }
+
         const targetSummary = AbstractGenerator.abstract(abstractPayload);
 
         this._log.trace('[getSubstanceAbstract:result] %s', targetSummary);
@@ -197,6 +428,22 @@ class Substances {
         return targetSummary;
     }
 
+    * getSubstanceLastModified({substance}) {
+        this._log.trace('[getSubstanceLastModified] substance: %s', substance);
+
+        // `touched` of prop=info also changes on cache purges, the last revision does not
+        const revisionPayload = yield* this._connector.get({
+            action: 'query',
+            prop: 'revisions',
+            rvprop: 'timestamp',
+            titles: substance
+        });
+
+        const page = _.head(_.values(_.get(revisionPayload, 'query.pages', {})));
+
+        return _.get(page, 'revisions[0].timestamp', null);
+    }
+
     * getSubstanceImages({substance}) {
         this._log.trace('[getSubstanceImages] substance: %s', substance);
 
@@ -206,6 +453,12 @@ class Substances {
             prop: 'images'
         });
 
+        if (Substances._isMissingPage(imagePayload)) {
+            this._log.trace('[getSubstanceImages] missing article: %s', substance);
+
+            return null;
+        }
+
         const images = _.get(imagePayload, 'parse.images', null);
 
         this._log.trace('[getSubstanceImages:result] %s', images);
@@ -214,7 +467,70 @@ class Substances {
             return null;
         }
 
-        return images.map(buildImage);
+        return images
+            .filter(isValidFileName)
+            .map(buildImage);
+    }
+
+    * getSubstanceSuggestions({query, limit}) {
+        this._log.trace('[getSubstanceSuggestions] query: %s', query);
+
+        const res = yield* this._connector.get({
+            query: `[[Category:Psychoactive substance]]${Substances._renderPagination({limit: SUBSTANCE_INDEX_LIMIT})}`
+        });
+
+        const results = _.get(res, 'query.results', {});
+
+        const normalizedQuery = query.trim().toLowerCase();
+
+        return _.chain(this._mapTextUrl(results))
+            .sortBy(item => levenshtein(normalizedQuery, item.name.toLowerCase()))
+            .take(limit)
+            .value();
+    }
+
+    * getRelatedSubstances({substance, limit}) {
+        this._log.trace('[getRelatedSubstances] substance: %s', substance);
+
+        /*
+         * A single query yields the effects of every substance;
+         * candidates are ranked by the Jaccard similarity of
+         * their effect set with the one of the target.
+         */
+        const res = yield* this._connector.get({
+            query: `[[Category:Psychoactive substance]]|?Effect${Substances._renderPagination({limit: SUBSTANCE_INDEX_LIMIT})}`
+        });
+
+        const results = _.get(res, 'query.results', {});
+
+        const effectSets = _.mapValues(results, item =>
+            new Set(_.map(_.get(item, 'printouts.Effect', []), 'fulltext'))
+        );
+
+        const target = _.findKey(results, (item, name) =>
+            name.toLowerCase() === substance.toLowerCase()
+        );
+
+        if (target === undefined || !effectSets[target].size) {
+            return [];
+        }
+
+        const targetEffects = effectSets[target];
+
+        return _.chain(results)
+            .omit(target)
+            .map((item, name) => {
+                const shared = _.filter([...effectSets[name]], effect => targetEffects.has(effect)).length;
+                const union = targetEffects.size + effectSets[name].size - shared;
+
+                return [item, shared / union];
+            })
+            .filter(([, score]) => score > 0)
+            .orderBy(([, score]) => score, 'desc')
+            .take(limit)
+            .map(([item]) => item)
+            .thru(items => this._mapTextUrl(items))
+            .value();
     }
 
     * getEffects({substance, query, limit, offset}) {
@@ -256,6 +572,45 @@ class Substances {
         return this._mapTextUrl(results);
     }
 
+    * getFilteredSubstances({effect, chemicalClass, psychoactiveClass, limit, offset}) {
+        this._log.trace('[getFilteredSubstances] effect: %s chemicalClass: %s psychoactiveClass: %s', effect, chemicalClass, psychoactiveClass);
+
+        const conditions = effect.map(effectName => `[[Effect::${effectName}]]`);
+
+        if (chemicalClass) {
+            conditions.push(`[[Chemical class::${chemicalClass}]]`);
+        }
+
+        if (psychoactiveClass) {
+            conditions.push(`[[Psychoactive class::${psychoactiveClass}]]`);
+        }
+
+        const res = yield* this._connector.get({
+            query: `${conditions.join('')}|[[Category:Psychoactive substance]]${Substances._renderPagination({limit, offset})}`
+        });
+
+        const results = _.get(res, 'query.results', {});
+
+        return this._mapTextUrl(results);
+    }
+
+    * getClassSubstances({name, limit, offset}) {
+        this._log.trace('[getClassSubstances] class: %s', name);
+
+        const classQuery = Substances._renderDisjunction([
+            `[[Chemical class::${name}]]`,
+            `[[Psychoactive class::${name}]]`
+        ]);
+
+        const res = yield* this._connector.get({
+            query: `${classQuery}|[[Category:Psychoactive substance]]${Substances._renderPagination({limit, offset})}`
+        });
+
+        const results = _.get(res, 'query.results', {});
+
+        return this._mapTextUrl(results);
+    }
+
     * getChemicalClassSubstances({chemicalClass, limit, offset}) {
         this._log.trace('[getChemicalClassSubstances] effect: %s', chemicalClass);
 
@@ -1,8 +1,13 @@
 'use strict';
 
+const _ = require('lodash');
+
 const log = require('./log');
 
-log.info(require('./util/ac'));
+// the banner only clutters machine-readable log pipelines
+if (!_.has(process.env, 'NO_BANNER')) {
+    log.info(require('./util/ac'));
+}
 
 require('./testbed-bootstrap')(log);
 
@@ -12,8 +17,18 @@ const express = require('express');
 const app = express();
 
 const graphRoutes = require('./services/graph');
+const statusRoutes = require('./services/status');
+const securityHeaders = require('./services/security');
 
 async(function* () {
+    yield* securityHeaders({
+        app, log
+    });
+
+    yield* statusRoutes({
+        app, log
+    });
+
     yield* graphRoutes({
         app, log
     });
@@ -2,7 +2,20 @@
 
 const bunyan = require('bunyan');
 
+const streams = [{stream: process.stdout}];
+
+// persist logs for deployments without log shipping
+if (process.env.LOG_FILE) {
+    streams.push({
+        type: 'rotating-file',
+        path: process.env.LOG_FILE,
+        period: process.env.LOG_FILE_PERIOD || '1d',
+        count: parseInt(process.env.LOG_FILE_COUNT, 10) || 7
+    });
+}
+
 module.exports = bunyan.createLogger({
     name: 'bifrost',
-    level: process.env.LOG_LEVEL || 'info'
+    level: process.env.LOG_LEVEL || 'info',
+    streams
 });
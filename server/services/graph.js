@@ -2,10 +2,15 @@
 
 const _ = require('lodash');
 
+const bodyParser = require('body-parser');
+const cors = require('cors');
+const express = require('express');
 const { ApolloServer } = require('apollo-server-express');
 
 const featureContext = {};
 
+const SUPPORTED_CONTENT_TYPES = ['application/json', 'application/graphql', 'multipart/form-data'];
+
 const features = require('../util/features');
 
 if (features.has('plebiscite')) {
@@ -14,7 +19,13 @@ if (features.has('plebiscite')) {
     });
 }
 
+const constants = require('../util/constants');
+const playground = require('../util/playground');
+
 const querySchema = require('../graph');
+const ResponseCache = require('../graph/responseCache');
+
+const responseCacheTtl = constants.get('responseCacheTtl');
 
 const Connector = require('../graph/storage/connector');
 const {Substances} = require('../graph/storage/models');
@@ -22,15 +33,24 @@ const {Substances} = require('../graph/storage/models');
 const SMWDataArbitrator = require('../graph/helpers/smwDataArbitrator');
 const smwDataArbitrator = new SMWDataArbitrator();
 
+const SubstanceBoxParser = require('../graph/helpers/substanceBoxParser');
+const substanceBoxParser = new SubstanceBoxParser({
+    smwDataArbitrator
+});
+
 const PWPropParser = require('../graph/helpers/pwPropParser');
 
 const pwPropParser = new PWPropParser({
-    smwDataArbitrator
+    smwDataArbitrator,
+    substanceBoxParser
 });
 
 module.exports = function* ({app, log}) {
     const baseQuerySchema = querySchema({log});
 
+    // allow public deployments to hide the schema from scrapers
+    const introspection = !_.has(process.env, 'DISABLE_INTROSPECTION');
+
     const server = new ApolloServer({
         schema: baseQuerySchema.schema,
         context: _.assign({}, {
@@ -50,7 +70,7 @@ module.exports = function* ({app, log}) {
         },
 
         debug: true,
-        playground: {
+        playground: _.has(process.env, 'DISABLE_PLAYGROUND') ? false : _.assign({
             tabs: [
                 {
                   query: `{
@@ -108,11 +128,69 @@ module.exports = function* ({app, log}) {
                   endpoint: '/',
                 },
             ]
-        },
-        introspection: true,
+        }, playground.options, introspection ? {} : {
+            // the playground keeps working, but must not poll the hidden schema
+            settings: {
+                'schema.polling.enable': false
+            }
+        }),
+        introspection,
 
         tracing: true,
         cacheControl: false,
+
+        plugins: responseCacheTtl ? [
+            new ResponseCache({
+                ttl: responseCacheTtl * 1000,
+                maxEntries: constants.get('responseCacheMaxEntries')
+            }).plugin()
+        ] : [],
+    });
+
+    if (playground.assetsDir) {
+        app.use(playground.assetsRoute, express.static(playground.assetsDir));
+    }
+
+    // without a playground, apollo would answer `GET /` with a bare 400
+    if (_.has(process.env, 'DISABLE_PLAYGROUND')) {
+        app.get('/', (req, res, next) => {
+            if (_.has(req.query, 'query')) {
+                return next();
+            }
+
+            res.status(404).json({
+                errors: [{
+                    message: 'The playground is disabled. Send GraphQL queries as a POST request or as the `query` parameter of a GET request.'
+                }]
+            });
+        });
+    }
+
+    // mounted ahead of apollo, so it has to answer with CORS headers of its own
+    app.post('/', cors(), (req, res, next) => {
+        // requests without a body are left for apollo to report
+        if (req.is(SUPPORTED_CONTENT_TYPES) !== false) {
+            return next();
+        }
+
+        res.status(415).json({
+            errors: [{
+                message: `Unsupported Content-Type '${req.get('content-type')}', expected one of: ${SUPPORTED_CONTENT_TYPES.join(', ')}.`
+            }]
+        });
+    });
+
+    /*
+     * `application/graphql` bodies carry the bare query; wrap
+     * them into the JSON request shape apollo expects. Once
+     * parsed, apollo's own JSON body parser skips the request.
+     */
+    app.post('/', bodyParser.text({type: 'application/graphql'}), (req, res, next) => {
+        if (req.is('application/graphql')) {
+            req.body = {query: req.body};
+        }
+
+        next();
     });
 
     server.applyMiddleware({
@@ -0,0 +1,57 @@
+'use strict';
+
+const {URL} = require('url');
+
+const playground = require('../util/playground');
+
+/*
+ * The playground page loads its bundle and styles from
+ * jsDelivr (or the configured mirror or local build) and
+ * relies on inline bootstrapping code, so only those
+ * sources are permitted.
+ */
+const playgroundCdnSource = cdnUrl => {
+    // apollo loads the playground from the protocol-relative `//cdn.jsdelivr.net/npm`
+    if (!cdnUrl) {
+        return 'cdn.jsdelivr.net';
+    }
+
+    // protocol-relative URLs translate to a scheme-less host source
+    if (cdnUrl.startsWith('//')) {
+        return new URL(`http:${cdnUrl}`).host;
+    }
+
+    // relative paths, i.e. the local build, are served from this origin
+    if (!/^https?:\/\//.test(cdnUrl)) {
+        return '\'self\'';
+    }
+
+    return new URL(cdnUrl).origin;
+};
+
+const playgroundCdn = playgroundCdnSource(playground.cdnUrl);
+
+const contentSecurityPolicy = [
+    'default-src \'self\'',
+    `script-src 'self' 'unsafe-inline' ${playgroundCdn}`,
+    `style-src 'self' 'unsafe-inline' ${playgroundCdn} https://fonts.googleapis.com`,
+    'font-src \'self\' data: https://fonts.gstatic.com',
+    `img-src 'self' data: ${playgroundCdn}`,
+    'connect-src \'self\'',
+    'frame-ancestors \'none\''
+].join('; ');
+
+module.exports = function* ({app}) {
+    app.disable('x-powered-by');
+
+    app.use((req, res, next) => {
+        res.set({
+            'Content-Security-Policy': contentSecurityPolicy,
+            'X-Content-Type-Options': 'nosniff',
+            'X-Frame-Options': 'DENY',
+            'Referrer-Policy': 'no-referrer'
+        });
+
+        next();
+    });
+};
@@ -0,0 +1,36 @@
+'use strict';
+
+const Promise = require('bluebird');
+
+const features = require('../util/features');
+
+const Connector = require('../graph/storage/connector');
+
+const plebisciteStatus = Promise.coroutine(function* () {
+    if (!features.has('plebiscite')) {
+        return {enabled: false};
+    }
+
+    const plebiscite = require('../features/plebiscite');
+
+    return {
+        enabled: true,
+        available: yield* plebiscite.isAvailable()
+    };
+});
+
+module.exports = function* ({app, log}) {
+    app.get('/debug/status', (req, res) => {
+        plebisciteStatus()
+            .then(plebiscite => res.json({
+                uptime: process.uptime(),
+                cache: Connector.cacheStats(),
+                plebiscite
+            }))
+            .catch(err => {
+                log.error(err, 'Could not compile status');
+
+                res.status(500).json({error: err.message});
+            });
+    });
+};
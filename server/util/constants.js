@@ -2,5 +2,15 @@
 
 module.exports = new Map([
     ['cdn', 'https://psychonautwiki.org/'],
-    ['thumbSize', 100]
+    ['thumbSize', 100],
+    ['thumbSizes', [100, 200, 400]],
+    // placeholders: {cdn}, {filename}, {width}, {hash1}, {hash2}
+    ['thumbTemplate', process.env.THUMB_URL_TEMPLATE || '{cdn}w/thumb.php?f={filename}&width={width}'],
+    ['imageTemplate', process.env.IMAGE_URL_TEMPLATE || '{cdn}w/images/{hash1}/{hash2}/{filename}'],
+    ['maxLimit', parseInt(process.env.MAX_LIMIT, 10) || 200],
+    ['maxOffset', parseInt(process.env.MAX_OFFSET, 10) || 10000],
+    ['upstreamConcurrency', parseInt(process.env.UPSTREAM_CONCURRENCY, 10) || Infinity],
+    // seconds, the response cache is disabled unless set
+    ['responseCacheTtl', parseInt(process.env.RESPONSE_CACHE_TTL, 10) || 0],
+    ['responseCacheMaxEntries', parseInt(process.env.RESPONSE_CACHE_MAX_ENTRIES, 10) || 1000]
 ]);
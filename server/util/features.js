@@ -5,7 +5,13 @@ const _ = require('lodash');
 const features = new Map([
     ['plebiscite', new Map([
         ['required', ['MONGO_URL']],
-        ['optional', [['MONGO_COLLECTION', 'plebiscite']]]
+        ['optional', [
+            ['MONGO_COLLECTION', 'plebiscite'],
+            ['MONGO_POOL_SIZE', '5'],
+            ['MONGO_MIN_POOL_SIZE', '0'],
+            ['MONGO_CONNECT_TIMEOUT_MS', '10000'],
+            ['MONGO_SERVER_SELECTION_TIMEOUT_MS', '30000']
+        ]]
     ])]
 ]);
 
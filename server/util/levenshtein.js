@@ -0,0 +1,27 @@
+'use strict';
+
+module.exports = (source, target) => {
+    if (source === target) {
+        return 0;
+    }
+
+    let previous = Array.from({length: target.length + 1}, (_, i) => i);
+
+    for (let i = 1; i <= source.length; ++i) {
+        const current = [i];
+
+        for (let j = 1; j <= target.length; ++j) {
+            const substitution = source[i - 1] === target[j - 1] ? 0 : 1;
+
+            current[j] = Math.min(
+                previous[j] + 1,
+                current[j - 1] + 1,
+                previous[j - 1] + substitution
+            );
+        }
+
+        previous = current;
+    }
+
+    return previous[target.length];
+};
@@ -0,0 +1,39 @@
+'use strict';
+
+const path = require('path');
+
+// mount path of a self-hosted playground build
+const ASSETS_PATH = '/playground';
+
+/*
+ * The playground page is rendered by apollo, but its bundle
+ * is fetched from jsDelivr unless either:
+ *
+ * - `PLAYGROUND_ASSETS_DIR` points at an installed copy of
+ *   `@apollographql/graphql-playground-react`, which is then
+ *   served by this server, or
+ * - `PLAYGROUND_CDN_URL` points at a mirror of the npm package
+ *   tree hosted elsewhere by the operator.
+ */
+const assetsDir = process.env.PLAYGROUND_ASSETS_DIR || null;
+
+// pages request `<cdnUrl>/@apollographql/graphql-playground-react@<version>/build/...`
+const assetsVersion = assetsDir
+    ? require(path.resolve(assetsDir, 'package.json')).version
+    : null;
+
+const assetsRoute = assetsDir
+    ? `${ASSETS_PATH}/@apollographql/graphql-playground-react@${assetsVersion}`
+    : null;
+
+const cdnUrl = assetsDir ? ASSETS_PATH : process.env.PLAYGROUND_CDN_URL || null;
+
+module.exports = {
+    assetsDir,
+    assetsRoute,
+
+    // overrides of apollo's playground options
+    options: assetsDir ? {cdnUrl, version: assetsVersion} : {cdnUrl: cdnUrl || undefined},
+
+    cdnUrl
+};
@@ -0,0 +1,70 @@
+'use strict';
+
+const http = require('http');
+
+const express = require('express');
+const Promise = require('bluebird');
+
+const graphRoutes = require('../../server/services/graph');
+const log = require('../../server/log');
+
+/*
+ * Mounts the graph service on an ephemeral port. `env` is
+ * applied while the service is set up, as its flags are
+ * read at that point.
+ */
+const startGraphServer = Promise.coroutine(function* (env = {}) {
+    const app = express();
+
+    const previousEnv = Object.keys(env).map(flag => [flag, process.env[flag]]);
+
+    Object.assign(process.env, env);
+
+    try {
+        yield* graphRoutes({app, log});
+    } finally {
+        previousEnv.forEach(([flag, value]) => {
+            if (value === undefined) {
+                delete process.env[flag];
+            } else {
+                process.env[flag] = value;
+            }
+        });
+    }
+
+    const server = yield new Promise(resolve => {
+        const listener = app.listen(0, '127.0.0.1', () => resolve(listener));
+    });
+
+    const request = ({method, path = '/', headers = {}, body}) => new Promise((resolve, reject) => {
+        const req = http.request({
+            host: '127.0.0.1',
+            port: server.address().port,
+            method,
+            path,
+            headers
+        }, res => {
+            let data = '';
+
+            res.setEncoding('utf8');
+            res.on('data', chunk => {
+                data += chunk;
+            });
+            res.on('end', () => resolve({
+                status: res.statusCode,
+                headers: res.headers,
+                body: data
+            }));
+        });
+
+        req.on('error', reject);
+        req.end(body);
+    });
+
+    return {
+        request,
+        close: () => Promise.fromCallback(callback => server.close(callback))
+    };
+});
+
+module.exports = startGraphServer;
@@ -0,0 +1,13 @@
+'use strict';
+
+const PWPropParser = require('../../server/graph/helpers/pwPropParser');
+const SMWDataArbitrator = require('../../server/graph/helpers/smwDataArbitrator');
+const SubstanceBoxParser = require('../../server/graph/helpers/substanceBoxParser');
+
+// parser wired up the way the graph service does it
+module.exports = () => {
+    const smwDataArbitrator = new SMWDataArbitrator();
+    const substanceBoxParser = new SubstanceBoxParser({smwDataArbitrator});
+
+    return new PWPropParser({smwDataArbitrator, substanceBoxParser});
+};
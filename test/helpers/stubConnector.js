@@ -0,0 +1,36 @@
+'use strict';
+
+const Promise = require('bluebird');
+
+const PwConnector = require('../../server/graph/storage/connector');
+const log = require('../../server/log');
+
+/*
+ * Connector whose upstream is replaced by `respond`,
+ * which maps a request URL to a response body (or a
+ * promise thereof). Requested URLs are recorded.
+ */
+class StubConnector extends PwConnector {
+    constructor(respond) {
+        super({log});
+
+        this.urls = [];
+        this._respond = respond;
+    }
+
+    * _fetchUrl(url) {
+        this.urls.push(url);
+
+        const body = yield Promise.try(() => this._respond(url));
+
+        return {body};
+    }
+
+    fetch(args) {
+        return Promise.coroutine(function* (connector) {
+            return yield* connector.get(args);
+        })(this);
+    }
+}
+
+module.exports = StubConnector;
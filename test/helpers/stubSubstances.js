@@ -0,0 +1,40 @@
+'use strict';
+
+const Promise = require('bluebird');
+
+const {Substances} = require('../../server/graph/storage/models');
+const log = require('../../server/log');
+
+/*
+ * Substances model backed by a connector that answers with
+ * `respond(args)` and a prop parser that yields `semanticData`
+ * and `infoboxData` for every subject. Requested arguments
+ * are recorded.
+ */
+module.exports = ({respond, semanticData = {roas: [{name: 'oral'}]}, infoboxData = {}}) => {
+    const requests = [];
+
+    const connector = {
+        * get(args) {
+            requests.push(args);
+
+            return yield Promise.try(() => respond(args));
+        }
+    };
+
+    const pwPropParser = {
+        parseFromSMW: ({subject}) => Object.assign({subject}, semanticData),
+        parseFromWikitext: () => infoboxData,
+        infoboxTransclusion: () => null
+    };
+
+    const substances = new Substances({connector, pwPropParser, log});
+
+    return {
+        requests,
+        substances,
+        call: (method, args) => Promise.coroutine(function* () {
+            return yield* substances[method](args);
+        })()
+    };
+};
@@ -0,0 +1,111 @@
+'use strict';
+
+const assert = require('assert');
+const Promise = require('bluebird');
+
+const PwConnector = require('../../server/graph/storage/connector');
+const StubConnector = require('../helpers/stubConnector');
+
+describe('PwConnector', () => {
+    it('serves repeated requests from the cache', Promise.coroutine(function* () {
+        const connector = new StubConnector(() => ({query: {results: {}}}));
+
+        const hitsBefore = PwConnector.cacheStats().hit;
+
+        const first = yield connector.fetch({query: '[[:CacheHit]]'});
+        const second = yield connector.fetch({query: '[[:CacheHit]]'});
+
+        assert.deepStrictEqual(second, first);
+        assert.strictEqual(connector.urls.length, 1);
+        assert.strictEqual(PwConnector.cacheStats().hit, hitsBefore + 1);
+    }));
+
+    it('coalesces concurrent misses of the same item', Promise.coroutine(function* () {
+        const connector = new StubConnector(() =>
+            Promise.delay(10).then(() => ({query: {results: {}}}))
+        );
+
+        const responses = yield Promise.all([
+            connector.fetch({query: '[[:Coalesced]]'}),
+            connector.fetch({query: '[[:Coalesced]]'})
+        ]);
+
+        assert.strictEqual(connector.urls.length, 1);
+        assert.strictEqual(responses[0], responses[1]);
+        assert.strictEqual(PwConnector.cacheStats().inflight, 0);
+    }));
+
+    it('releases coalesced waiters when the fetch fails', Promise.coroutine(function* () {
+        const connector = new StubConnector(() =>
+            Promise.delay(10).then(() => {
+                throw new Error('upstream down');
+            })
+        );
+
+        const outcomes = yield Promise.all([
+            connector.fetch({query: '[[:FailedFetch]]'}).reflect(),
+            connector.fetch({query: '[[:FailedFetch]]'}).reflect()
+        ]);
+
+        assert.ok(outcomes.every(outcome => outcome.isRejected()));
+        assert.strictEqual(PwConnector.cacheStats().inflight, 0);
+        assert.strictEqual(PwConnector.cacheStats().waiters, 0);
+    }));
+
+    it('rejects waiters beyond the configured cap right away', Promise.coroutine(function* () {
+        const connector = new StubConnector(() =>
+            Promise.delay(50).then(() => ({query: {results: {}}}))
+        );
+
+        connector._maxCoalescedWaiters = 1;
+
+        const rejectedBefore = PwConnector.cacheStats().rejected;
+
+        const leader = connector.fetch({query: '[[:CappedWaiters]]'}).reflect();
+        const waiter = connector.fetch({query: '[[:CappedWaiters]]'}).reflect();
+        const excess = connector.fetch({query: '[[:CappedWaiters]]'}).reflect();
+
+        const excessOutcome = yield excess;
+
+        assert.ok(excessOutcome.isRejected());
+        assert.ok(/Too many pending requests/.test(excessOutcome.reason().message));
+        assert.ok(leader.isPending() && waiter.isPending());
+        assert.strictEqual(PwConnector.cacheStats().rejected, rejectedBefore + 1);
+
+        const outcomes = yield Promise.all([leader, waiter]);
+
+        assert.ok(outcomes.every(outcome => outcome.isFulfilled()));
+        assert.strictEqual(connector.urls.length, 1);
+    }));
+
+    it('clears the refresh flag when a background refresh fails', Promise.coroutine(function* () {
+        let upstreamDown = false;
+
+        const connector = new StubConnector(() => {
+            if (upstreamDown) {
+                throw new Error('upstream down');
+            }
+
+            return {query: {results: {}}};
+        });
+
+        const fresh = yield connector.fetch({query: '[[:FailedRefresh]]'});
+
+        const [url] = connector.urls;
+
+        // expire the item so the next request triggers a refresh
+        connector._cache.add(url, fresh, -1);
+
+        upstreamDown = true;
+
+        const stale = yield connector.fetch({query: '[[:FailedRefresh]]'});
+
+        assert.strictEqual(stale, fresh);
+        assert.ok(connector._cache.isBeingRefreshed(url));
+
+        yield Promise.delay(10);
+
+        assert.strictEqual(connector.urls.length, 2);
+        assert.strictEqual(connector._cache.isBeingRefreshed(url), false);
+    }));
+});
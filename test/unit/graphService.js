@@ -0,0 +1,116 @@
+'use strict';
+
+const assert = require('assert');
+const Promise = require('bluebird');
+
+const pkg = require('../../package.json');
+
+const startGraphServer = require('../helpers/graphServer');
+
+describe('Graph service', () => {
+    let server;
+
+    before(Promise.coroutine(function* () {
+        server = yield startGraphServer();
+    }));
+
+    after(() => server.close());
+
+    it('rejects unsupported content types with CORS headers', Promise.coroutine(function* () {
+        const res = yield server.request({
+            method: 'POST',
+            headers: {
+                'content-type': 'text/plain',
+                origin: 'https://example.org'
+            },
+            body: '{ version { version } }'
+        });
+
+        assert.strictEqual(res.status, 415);
+        assert.strictEqual(res.headers['access-control-allow-origin'], '*');
+        assert.ok(/Unsupported Content-Type 'text\/plain'/.test(JSON.parse(res.body).errors[0].message));
+    }));
+
+    it('executes bare queries posted as application/graphql', Promise.coroutine(function* () {
+        const res = yield server.request({
+            method: 'POST',
+            headers: {
+                'content-type': 'application/graphql'
+            },
+            body: '{ version { version } }'
+        });
+
+        assert.strictEqual(res.status, 200);
+        assert.deepStrictEqual(JSON.parse(res.body).data, {
+            version: {version: pkg.version}
+        });
+    }));
+});
+
+describe('Graph service without playground', () => {
+    let server;
+
+    before(Promise.coroutine(function* () {
+        server = yield startGraphServer({DISABLE_PLAYGROUND: '1'});
+    }));
+
+    after(() => server.close());
+
+    it('answers the root page with a 404', Promise.coroutine(function* () {
+        const res = yield server.request({
+            method: 'GET',
+            headers: {
+                accept: 'text/html'
+            }
+        });
+
+        assert.strictEqual(res.status, 404);
+        assert.ok(/playground is disabled/.test(JSON.parse(res.body).errors[0].message));
+    }));
+
+    it('still executes queries passed as a parameter', Promise.coroutine(function* () {
+        const res = yield server.request({
+            method: 'GET',
+            path: `/?query=${encodeURIComponent('{ version { version } }')}`
+        });
+
+        assert.strictEqual(res.status, 200);
+        assert.deepStrictEqual(JSON.parse(res.body).data, {
+            version: {version: pkg.version}
+        });
+    }));
+});
+
+describe('Graph service without introspection', () => {
+    let server;
+
+    before(Promise.coroutine(function* () {
+        server = yield startGraphServer({DISABLE_INTROSPECTION: '1'});
+    }));
+
+    after(() => server.close());
+
+    it('rejects introspection queries', Promise.coroutine(function* () {
+        const res = yield server.request({
+            method: 'POST',
+            headers: {
+                'content-type': 'application/json'
+            },
+            body: JSON.stringify({query: '{ __schema { types { name } } }'})
+        });
+
+        assert.ok(JSON.parse(res.body).errors.length);
+    }));
+
+    it('serves a playground that does not poll the schema', Promise.coroutine(function* () {
+        const res = yield server.request({
+            method: 'GET',
+            headers: {
+                accept: 'text/html'
+            }
+        });
+
+        assert.strictEqual(res.status, 200);
+        assert.ok(/"schema\.polling\.enable":\s*false/.test(res.body));
+    }));
+});
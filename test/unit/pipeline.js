@@ -0,0 +1,83 @@
+'use strict';
+
+const assert = require('assert');
+const querystring = require('querystring');
+const Promise = require('bluebird');
+
+const {Substances} = require('../../server/graph/storage/models');
+const log = require('../../server/log');
+
+const StubConnector = require('../helpers/stubConnector');
+const createPropParser = require('../helpers/propParser');
+
+const lsdFixture = require('../fixtures/browsebysubject-lsd.json');
+
+// canned responses of the wiki API, keyed by action
+const mockWiki = url => {
+    const params = querystring.decode(url.split('?')[1]);
+
+    switch (params.action) {
+        case 'ask':
+            return {
+                query: {
+                    results: {
+                        LSD: {
+                            fulltext: 'LSD',
+                            fullurl: 'https://psychonautwiki.org/wiki/LSD',
+                            printouts: []
+                        }
+                    }
+                }
+            };
+
+        case 'browsebysubject':
+            return lsdFixture;
+
+        default:
+            throw new Error(`Unexpected request: ${url}`);
+    }
+};
+
+describe('Substances pipeline', () => {
+    it('resolves a substance from the wiki responses', Promise.coroutine(function* () {
+        const connector = new StubConnector(mockWiki);
+
+        const substances = new Substances({
+            connector,
+            pwPropParser: createPropParser(),
+            log
+        });
+
+        const [substance, ...rest] = yield* substances.getSubstances({
+            query: 'LSD',
+            limit: 1,
+            offset: 0
+        });
+
+        assert.strictEqual(rest.length, 0);
+        assert.deepStrictEqual(
+            connector.urls.map(url => querystring.decode(url.split('?')[1]).action),
+            ['ask', 'browsebysubject']
+        );
+
+        assert.strictEqual(substance.name, 'LSD');
+        assert.strictEqual(substance.url, 'https://psychonautwiki.org/wiki/LSD');
+        assert.deepStrictEqual(substance.effects, ['Pupil dilation', 'Time distortion']);
+        assert.deepStrictEqual(substance.class, {
+            chemical: ['Lysergamides'],
+            psychoactive: ['Psychedelics']
+        });
+        assert.deepStrictEqual(substance.roas, [{
+            dose: {
+                units: 'µg',
+                threshold: 15,
+                common: {min: 75, max: 150}
+            },
+            duration: {
+                onset: {min: 20, max: 60, units: 'minutes'}
+            },
+            bioavailability: {min: 71},
+            name: 'oral'
+        }]);
+    }));
+});
@@ -0,0 +1,73 @@
+'use strict';
+
+const assert = require('assert');
+const Promise = require('bluebird');
+
+const plebiscite = require('../../server/features/plebiscite');
+
+const mongoError = (name, code) => {
+    const err = new Error(name);
+
+    err.name = name;
+    err.code = code;
+
+    return err;
+};
+
+// runs `operation` through the retry loop, recording its attempts
+const withRetry = failures => {
+    let attempts = 0;
+
+    const operation = () => {
+        const failure = failures[attempts++];
+
+        return failure ? Promise.reject(failure) : Promise.resolve('result');
+    };
+
+    return Promise.coroutine(function* () {
+        return yield* plebiscite._withRetry(operation);
+    })().reflect().then(outcome => ({outcome, attempts}));
+};
+
+describe('Plebiscite', () => {
+    describe('_withRetry', () => {
+        it('retries network errors and not-master errors', Promise.coroutine(function* () {
+            const {outcome, attempts} = yield withRetry([
+                mongoError('MongoNetworkError'),
+                mongoError('MongoError', 10107)
+            ]);
+
+            assert.strictEqual(outcome.value(), 'result');
+            assert.strictEqual(attempts, 3);
+        }));
+
+        it('gives up after the last attempt', Promise.coroutine(function* () {
+            const {outcome, attempts} = yield withRetry([
+                mongoError('MongoNetworkError'),
+                mongoError('MongoNetworkError'),
+                mongoError('MongoNetworkError')
+            ]);
+
+            assert.ok(outcome.isRejected());
+            assert.strictEqual(attempts, 3);
+        }));
+
+        it('does not retry server selection timeouts', Promise.coroutine(function* () {
+            const {outcome, attempts} = yield withRetry([
+                mongoError('MongoTimeoutError')
+            ]);
+
+            assert.strictEqual(outcome.reason().name, 'MongoTimeoutError');
+            assert.strictEqual(attempts, 1);
+        }));
+
+        it('does not retry other errors', Promise.coroutine(function* () {
+            const {outcome, attempts} = yield withRetry([
+                mongoError('MongoError', 2)
+            ]);
+
+            assert.ok(outcome.isRejected());
+            assert.strictEqual(attempts, 1);
+        }));
+    });
+});
@@ -0,0 +1,53 @@
+'use strict';
+
+const assert = require('assert');
+
+const createPropParser = require('../helpers/propParser');
+
+const lsdFixture = require('../fixtures/browsebysubject-lsd.json');
+
+describe('PWPropParser', () => {
+    describe('parseFromSMW', () => {
+        it('maps a browsebysubject payload onto the substance shape', () => {
+            const oral = {
+                dose: {
+                    units: 'µg',
+                    threshold: 15,
+                    common: {min: 75, max: 150}
+                },
+                duration: {
+                    onset: {min: 20, max: 60, units: 'minutes'}
+                },
+                bioavailability: {min: 71},
+                name: 'oral'
+            };
+
+            assert.deepStrictEqual(createPropParser().parseFromSMW(lsdFixture), {
+                addictionPotential: 'not habit-forming',
+                class: {
+                    chemical: ['Lysergamides'],
+                    psychoactive: ['Psychedelics']
+                },
+                effects: ['Pupil dilation', 'Time distortion'],
+                featured: false,
+                roa: {oral},
+                roas: [oral],
+                tolerance: {
+                    full: 'almost immediately after ingestion'
+                },
+                toxicity: ['extremely low']
+            });
+        });
+    });
+
+    describe('parse', () => {
+        it('exposes null routes for substances without route data', () => {
+            const parsed = createPropParser().parse(['Placebo', [
+                ['Toxicity', {prop: 'none'}]
+            ]]);
+
+            assert.strictEqual(parsed.roa, null);
+            assert.strictEqual(parsed.roas, null);
+        });
+    });
+});
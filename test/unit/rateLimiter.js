@@ -0,0 +1,88 @@
+'use strict';
+
+const assert = require('assert');
+const Promise = require('bluebird');
+
+const {UpstreamRateLimiter} = require('../../server/graph/storage/connector');
+
+/*
+ * Runs `fn` with a frozen clock, recording the delays the
+ * limiter asks for instead of waiting for them.
+ */
+const withFrozenClock = Promise.coroutine(function* (fn) {
+    const {now} = Date;
+    const {delay} = Promise;
+
+    const clock = {time: now(), delays: []};
+
+    Date.now = () => clock.time;
+    Promise.delay = ms => {
+        clock.delays.push(ms);
+
+        return Promise.resolve();
+    };
+
+    try {
+        yield fn(clock);
+    } finally {
+        Date.now = now;
+        Promise.delay = delay;
+    }
+
+    return clock.delays;
+});
+
+const acquire = limiter => Promise.coroutine(function* () {
+    yield* limiter.acquire();
+})();
+
+describe('UpstreamRateLimiter', () => {
+    it('lets a burst pass and spaces out the requests beyond it', Promise.coroutine(function* () {
+        const delays = yield withFrozenClock(() => {
+            const limiter = new UpstreamRateLimiter({rps: 2, burst: 2});
+
+            return Promise.all([1, 2, 3, 4].map(() => acquire(limiter)));
+        });
+
+        assert.deepStrictEqual(delays, [500, 1000]);
+    }));
+
+    it('refills tokens over time up to the burst size', Promise.coroutine(function* () {
+        const delays = yield withFrozenClock(Promise.coroutine(function* (clock) {
+            const limiter = new UpstreamRateLimiter({rps: 2, burst: 2});
+
+            yield acquire(limiter);
+            yield acquire(limiter);
+
+            clock.time += 10 * 1000;
+
+            yield acquire(limiter);
+            yield acquire(limiter);
+            yield acquire(limiter);
+        }));
+
+        assert.deepStrictEqual(delays, [500]);
+    }));
+
+    it('releases waiting requests in order', Promise.coroutine(function* () {
+        const limiter = new UpstreamRateLimiter({rps: 100, burst: 1});
+
+        const released = [];
+
+        yield Promise.all([1, 2, 3].map(index =>
+            acquire(limiter).then(() => released.push(index))
+        ));
+
+        assert.deepStrictEqual(released, [1, 2, 3]);
+    }));
+
+    it('does not limit without a rate', Promise.coroutine(function* () {
+        const delays = yield withFrozenClock(() => {
+            const limiter = new UpstreamRateLimiter({rps: 0, burst: 0});
+
+            return Promise.all([1, 2, 3].map(() => acquire(limiter)));
+        });
+
+        assert.deepStrictEqual(delays, []);
+    }));
+});
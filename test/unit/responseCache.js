@@ -0,0 +1,98 @@
+'use strict';
+
+const assert = require('assert');
+
+const {parse} = require('graphql');
+
+const ResponseCache = require('../../server/graph/responseCache');
+
+// runs a request through the plugin hooks, `execute` stands in for apollo
+const run = (cache, {query, variables, operationName}, execute) => {
+    const document = parse(query);
+
+    const listeners = cache.plugin().requestDidStart();
+
+    const requestContext = {
+        request: {query, variables, operationName},
+        document,
+        operation: document.definitions[0]
+    };
+
+    const cached = listeners.responseForOperation(requestContext);
+    const response = cached || execute();
+
+    listeners.willSendResponse({response});
+
+    return {response, cached: cached !== null};
+};
+
+describe('ResponseCache', () => {
+    const query = '{ substances(query: "LSD") { name } }';
+    const execute = () => ({data: {substances: [{name: 'LSD'}]}});
+
+    it('serves repeated queries from the cache', () => {
+        const cache = new ResponseCache({ttl: 1000, maxEntries: 10});
+
+        assert.strictEqual(run(cache, {query}, execute).cached, false);
+
+        const {response, cached} = run(cache, {query: `\n${query}\n`}, execute);
+
+        assert.strictEqual(cached, true);
+        assert.deepStrictEqual(response, execute());
+    });
+
+    it('keys responses by variables and operation name', () => {
+        const cache = new ResponseCache({ttl: 1000, maxEntries: 10});
+
+        const request = {query: 'query Lookup($q: String) { substances(query: $q) { name } }'};
+
+        run(cache, Object.assign({variables: {q: 'LSD'}}, request), execute);
+
+        assert.strictEqual(run(cache, Object.assign({variables: {q: 'DMT'}}, request), execute).cached, false);
+        assert.strictEqual(run(cache, Object.assign({variables: {q: 'LSD'}, operationName: 'Lookup'}, request), execute).cached, false);
+    });
+
+    it('does not cache responses with errors', () => {
+        const cache = new ResponseCache({ttl: 1000, maxEntries: 10});
+
+        run(cache, {query}, () => ({data: null, errors: [{message: 'upstream down'}]}));
+
+        assert.strictEqual(run(cache, {query}, execute).cached, false);
+    });
+
+    it('expires responses after their lifetime', () => {
+        const cache = new ResponseCache({ttl: 1000, maxEntries: 10});
+
+        const {now} = Date;
+        const time = now();
+
+        try {
+            Date.now = () => time;
+
+            run(cache, {query}, execute);
+
+            Date.now = () => time + 1001;
+
+            assert.strictEqual(run(cache, {query}, execute).cached, false);
+        } finally {
+            Date.now = now;
+        }
+    });
+
+    it('evicts the oldest response beyond the maximum size', () => {
+        const cache = new ResponseCache({ttl: 1000, maxEntries: 1});
+
+        run(cache, {query}, execute);
+        run(cache, {query: '{ effects { name } }'}, execute);
+
+        assert.strictEqual(run(cache, {query}, execute).cached, false);
+    });
+
+    it('does not cache mutations', () => {
+        const cache = new ResponseCache({ttl: 1000, maxEntries: 10});
+
+        run(cache, {query: 'mutation { vote }'}, execute);
+
+        assert.strictEqual(run(cache, {query: 'mutation { vote }'}, execute).cached, false);
+    });
+});
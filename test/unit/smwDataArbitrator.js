@@ -0,0 +1,57 @@
+'use strict';
+
+const assert = require('assert');
+
+const SMWDataArbitrator = require('../../server/graph/helpers/smwDataArbitrator');
+
+describe('SMWDataArbitrator', () => {
+    describe('parseNumber', () => {
+        const smwDataArbitrator = new SMWDataArbitrator();
+
+        [
+            ['~5', 5],
+            ['1,000', 1000],
+            ['100mg', 100],
+            ['1,000mg', 1000],
+            ['0.5', 0.5],
+            [12, 12]
+        ].forEach(([item, expected]) => {
+            it(`parses \`${item}\``, () => {
+                assert.strictEqual(smwDataArbitrator.parseNumber(item), expected);
+            });
+        });
+
+        it('returns null for values without a number', () => {
+            assert.strictEqual(smwDataArbitrator.parseNumber('unknown'), null);
+        });
+    });
+
+    describe('parseItemList', () => {
+        const smwDataArbitrator = new SMWDataArbitrator();
+
+        it('maps data items by their type', () => {
+            assert.deepStrictEqual(smwDataArbitrator.parseItemList([
+                {property: 'Oral_threshold_dose', dataitem: [{type: 1, item: '15'}]},
+                {property: 'Toxicity', dataitem: [{type: 2, item: 'extremely low'}]},
+                {property: 'Featured', dataitem: [{type: 4, item: 't'}]},
+                {property: 'Dosage_record', dataitem: [{type: 8, item: 'LSD#0##_abc'}]},
+                {property: 'Effect', dataitem: [{type: 9, item: 'Pupil_dilation#0##'}, {type: 9, item: 'Euphoria#0##'}]},
+                {property: '_SKEY', dataitem: [{type: 2, item: 'LSD'}]}
+            ]), [
+                ['Oral_threshold_dose', {type: 'number', prop: 15}],
+                ['Toxicity', {type: 'string', prop: 'extremely low'}],
+                ['Featured', {type: 'boolean', prop: true}],
+                ['Dosage_record', {type: 'container', prop: 'LSD#_abc'}],
+                ['Effect', {type: 'property', prop: ['Pupil_dilation#', 'Euphoria#']}]
+            ]);
+        });
+
+        it('leaves unknown types untouched', () => {
+            assert.deepStrictEqual(smwDataArbitrator.parseItemList([
+                {property: 'Location', dataitem: [{type: 5, item: '1,2'}]}
+            ]), [
+                ['Location', {type: null, prop: '1,2'}]
+            ]);
+        });
+    });
+});
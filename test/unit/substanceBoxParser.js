@@ -0,0 +1,50 @@
+'use strict';
+
+const assert = require('assert');
+
+const SMWDataArbitrator = require('../../server/graph/helpers/smwDataArbitrator');
+const SubstanceBoxParser = require('../../server/graph/helpers/substanceBoxParser');
+
+const createPropParser = require('../helpers/propParser');
+
+describe('SubstanceBoxParser', () => {
+    const substanceBoxParser = new SubstanceBoxParser({
+        smwDataArbitrator: new SMWDataArbitrator()
+    });
+
+    describe('parse', () => {
+        it('extracts the parameters of an inline box', () => {
+            const wikitext = '{{SubstanceBox\n|Oral_threshold_dose = 1,000mg\n|Oral_dose_units = mg\n}}\nLSD is a [[psychedelic]].';
+
+            assert.deepStrictEqual(substanceBoxParser.parse('LSD', wikitext), ['LSD', [
+                ['Oral_threshold_dose', {prop: 1000}],
+                ['Oral_dose_units', {prop: 'mg'}]
+            ]]);
+        });
+
+        it('ignores transclusions of the box', () => {
+            assert.deepStrictEqual(substanceBoxParser.parse('LSD', '{{SubstanceBox/LSD}}'), ['LSD', []]);
+        });
+    });
+
+    describe('transclusionTarget', () => {
+        it('resolves the template page of a transcluded box', () => {
+            assert.strictEqual(substanceBoxParser.transclusionTarget('{{SubstanceBox/LSD}}\nLSD is'), 'Template:SubstanceBox/LSD');
+        });
+
+        it('returns null for an inline box', () => {
+            assert.strictEqual(substanceBoxParser.transclusionTarget('{{SubstanceBox\n|Oral_threshold_dose=15}}'), null);
+        });
+    });
+
+    describe('parseFromWikitext', () => {
+        it('drops non-numeric values of numeric properties', () => {
+            const wikitext = '{{SubstanceBox\n|Oral_threshold_dose = 10-20mg\n|Oral_min_common_dose = 20\n|Oral_dose_units = mg\n}}';
+
+            assert.deepStrictEqual(createPropParser().parseFromWikitext('LSD', wikitext).roa.oral.dose, {
+                common: {min: 20},
+                units: 'mg'
+            });
+        });
+    });
+});
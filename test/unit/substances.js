@@ -0,0 +1,241 @@
+'use strict';
+
+const assert = require('assert');
+const crypto = require('crypto');
+const Promise = require('bluebird');
+
+const constants = require('../../server/util/constants');
+const createPropParser = require('../helpers/propParser');
+const stubSubstances = require('../helpers/stubSubstances');
+
+const askResult = (name, printouts) => ({
+    fulltext: name,
+    fullurl: `https://psychonautwiki.org/wiki/${name}`,
+    printouts
+});
+
+describe('Substances', () => {
+    describe('getBioavailabilitySubstances', () => {
+        const respond = args => {
+            if (args.action === 'browsebysubject') {
+                return {query: {subject: args.subject}};
+            }
+
+            return {
+                query: {
+                    results: {
+                        Alpha: askResult('Alpha', {min: [80], max: [90]}),
+                        Beta: askResult('Beta', {min: [], max: [20]}),
+                        Gamma: askResult('Gamma', {min: [40], max: []}),
+                        Delta: askResult('Delta', {min: [], max: []})
+                    }
+                }
+            };
+        };
+
+        it('sorts by the midpoint of partial ranges', Promise.coroutine(function* () {
+            const {call} = stubSubstances({respond});
+
+            const result = yield call('getBioavailabilitySubstances', {roa: 'oral', limit: 10, offset: 0});
+
+            assert.deepStrictEqual(result.map(({name}) => name), ['Beta', 'Gamma', 'Alpha']);
+        }));
+
+        it('asks for the bounds and only resolves the requested page', Promise.coroutine(function* () {
+            const {call, requests} = stubSubstances({respond});
+
+            const result = yield call('getBioavailabilitySubstances', {roa: 'oral', limit: 1, offset: 1});
+
+            assert.deepStrictEqual(result.map(({name}) => name), ['Gamma']);
+
+            const [ask, ...semantic] = requests;
+
+            assert.ok(ask.query.includes('|?Oral_min_bioavailability=min|?Oral_max_bioavailability=max'));
+            assert.ok(ask.query.includes('|limit=5000'));
+            assert.deepStrictEqual(semantic.map(({subject}) => subject), ['Gamma']);
+        }));
+    });
+
+    describe('getSubstancesByNames', () => {
+        const respond = args => {
+            if (args.action === 'browsebysubject') {
+                return {query: {subject: args.subject}};
+            }
+
+            const name = args.query.slice(3, args.query.indexOf(']]'));
+
+            if (name === 'Missing') {
+                return {query: {results: []}};
+            }
+
+            return {query: {results: {[name]: askResult(name, {})}}};
+        };
+
+        it('preserves the input order and yields null for misses', Promise.coroutine(function* () {
+            const {call} = stubSubstances({respond});
+
+            const result = yield call('getSubstancesByNames', {names: ['LSD', 'Missing', 'DMT']});
+
+            assert.deepStrictEqual(result.map(item => item && item.name), ['LSD', null, 'DMT']);
+        }));
+
+        it('yields null for blank names without asking upstream', Promise.coroutine(function* () {
+            const {call, requests} = stubSubstances({respond});
+
+            const result = yield call('getSubstancesByNames', {names: ['', '  ']});
+
+            assert.deepStrictEqual(result, [null, null]);
+            assert.strictEqual(requests.length, 0);
+        }));
+
+        it('rejects more names than the maximum limit', Promise.coroutine(function* () {
+            const {call, requests} = stubSubstances({respond});
+
+            const outcome = yield call('getSubstancesByNames', {names: new Array(constants.get('maxLimit') + 1).fill('LSD')}).reflect();
+
+            assert.ok(outcome.isRejected());
+            assert.strictEqual(requests.length, 0);
+        }));
+    });
+
+    describe('getSubstanceInfobox', () => {
+        it('parses the box from its template page when transcluded', Promise.coroutine(function* () {
+            const pages = {
+                LSD: '{{SubstanceBox/LSD}}\nLSD is a [[psychedelic]].',
+                'Template:SubstanceBox/LSD': '{{SubstanceBox\n|Oral_threshold_dose=15\n}}'
+            };
+
+            const {substances, call, requests} = stubSubstances({
+                respond: ({page}) => ({parse: {wikitext: {'*': pages[page]}}})
+            });
+
+            const parsed = [];
+
+            substances._pwPropParser = createPropParser();
+            substances._pwPropParser.parse = propSet => {
+                parsed.push(propSet);
+
+                return {};
+            };
+
+            yield call('getSubstanceInfobox', 'LSD');
+
+            assert.deepStrictEqual(requests.map(({page}) => page), ['LSD', 'Template:SubstanceBox/LSD']);
+            assert.deepStrictEqual(parsed, [['LSD', [['Oral_threshold_dose', {prop: 15}]]]]);
+        }));
+    });
+
+    describe('getSubstanceImages', () => {
+        it('skips invalid file names', Promise.coroutine(function* () {
+            const {call} = stubSubstances({
+                respond: () => ({parse: {images: ['', 'a/b.png', 'Foo bar.png']}})
+            });
+
+            const hash = crypto.createHash('md5').update('Foo bar.png').digest('hex');
+
+            assert.deepStrictEqual(yield call('getSubstanceImages', {substance: 'LSD'}), [{
+                thumb: 'https://psychonautwiki.org/w/thumb.php?f=Foo%20bar.png&width=100',
+                thumbs: [100, 200, 400].map(width => ({
+                    width,
+                    url: `https://psychonautwiki.org/w/thumb.php?f=Foo%20bar.png&width=${width}`
+                })),
+                image: `https://psychonautwiki.org/w/images/${hash[0]}/${hash.slice(0, 2)}/Foo%20bar.png`
+            }]);
+        }));
+
+        it('returns null for missing articles', Promise.coroutine(function* () {
+            const {call} = stubSubstances({
+                respond: () => ({error: {code: 'missingtitle'}})
+            });
+
+            assert.strictEqual(yield call('getSubstanceImages', {substance: 'Missing'}), null);
+        }));
+    });
+
+    describe('getSubstanceLastModified', () => {
+        it('returns the timestamp of the last revision', Promise.coroutine(function* () {
+            const {call, requests} = stubSubstances({
+                respond: () => ({
+                    query: {
+                        pages: {
+                            1: {
+                                title: 'LSD',
+                                touched: '2020-04-02T00:00:00Z',
+                                revisions: [{timestamp: '2020-03-01T12:00:00Z'}]
+                            }
+                        }
+                    }
+                })
+            });
+
+            assert.strictEqual(yield call('getSubstanceLastModified', {substance: 'LSD'}), '2020-03-01T12:00:00Z');
+            assert.strictEqual(requests[0].rvprop, 'timestamp');
+        }));
+    });
+
+    describe('getSubstances', () => {
+        const respond = () => ({query: {results: {LSD: askResult('LSD', {})}}});
+
+        it('accepts a single effect name', Promise.coroutine(function* () {
+            const {call, requests} = stubSubstances({respond});
+
+            yield call('getSubstances', {effect: 'Euphoria', limit: 10, offset: 0});
+
+            assert.ok(requests[0].query.startsWith('[[Effect::Euphoria]]|[[Category:Psychoactive substance]]'));
+        }));
+
+        it('intersects a single effect name with other filters', Promise.coroutine(function* () {
+            const {call, requests} = stubSubstances({respond});
+
+            yield call('getSubstances', {effect: 'Euphoria', psychoactiveClass: 'Psychedelics', limit: 10, offset: 0});
+
+            assert.ok(requests[0].query.startsWith('[[Effect::Euphoria]][[Psychoactive class::Psychedelics]]|'));
+        }));
+    });
+
+    describe('getSemanticSubstanceProps', () => {
+        const semanticData = {
+            roa: null,
+            roas: null,
+            toxicity: ['semantic']
+        };
+
+        const infoboxData = {
+            roa: {oral: {name: 'oral'}},
+            roas: [{name: 'oral'}],
+            toxicity: ['infobox', 'second infobox']
+        };
+
+        it('prefers semantic values over the infobox as a whole', Promise.coroutine(function* () {
+            const {call} = stubSubstances({
+                respond: () => ({}),
+                semanticData,
+                infoboxData
+            });
+
+            const props = yield call('getSemanticSubstanceProps', 'LSD');
+
+            assert.deepStrictEqual(props.toxicity, ['semantic']);
+            assert.deepStrictEqual(props.roas, [{name: 'oral'}]);
+        }));
+
+        it('falls back to the semantic data if the infobox cannot be fetched', Promise.coroutine(function* () {
+            const {call} = stubSubstances({
+                respond: args => {
+                    if (args.action === 'parse') {
+                        throw new Error('upstream down');
+                    }
+
+                    return {};
+                },
+                semanticData,
+                infoboxData
+            });
+
+            const props = yield call('getSemanticSubstanceProps', 'LSD');
+
+            assert.deepStrictEqual(props.toxicity, ['semantic']);
+            assert.strictEqual(props.roas, null);
+        }));
+    });
+});